@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
 use superfusion::prelude::*;
@@ -11,11 +10,10 @@ pub enum Asset {
 }
 
 impl Asset {
-	pub fn new(path: impl Into<PathBuf>, pid: Pid) -> Result<Self, super::Error> {
-		let path = path.into();
-		let mut reader = File::open(&path)?;
+	pub fn new(index: &Index, mut reader: impl Read) -> Result<Self, super::Error> {
+		let pid = *index.pid();
 
-		let result = match path.extension().and_then(|os| os.to_str()) {
+		let result = match index.path().extension().and_then(|os| os.to_str()) {
 			Some("json") => {
 				let data: Data = serde_json::from_reader(reader)?;
 				let json = Json { pid, data };
@@ -40,20 +38,21 @@ impl superfusion::file::File for Asset {
 			Asset::Text(text) => text.relation(),
 		}
 	}
-	fn data(self) -> Vec<u8> {
+	fn data(self) -> Result<Vec<u8>, Error> {
 		match self {
 			Asset::Json(json) => json.data(),
 			Asset::Text(text) => text.data(),
 		}
 	}
-	fn modify_relation(self, from: &Index, to: &Index) -> Self
+	fn modify_relation(self, from: &Index, to: &Index) -> Result<Self, Error>
 	where
 		Self: Sized,
 	{
-		match self {
-			Asset::Json(json) => Asset::Json(json.modify_relation(from, to)),
-			Asset::Text(text) => Asset::Text(text.modify_relation(from, to)),
-		}
+		let result = match self {
+			Asset::Json(json) => Asset::Json(json.modify_relation(from, to)?),
+			Asset::Text(text) => Asset::Text(text.modify_relation(from, to)?),
+		};
+		Ok(result)
 	}
 	fn merge(self, other: Self) -> Result<Self, Error>
 	where
@@ -85,15 +84,15 @@ impl superfusion::file::File for Json {
 			.map(Relation::new)
 			.map_or(vec![], |r| vec![r])
 	}
-	fn data(self) -> Vec<u8> {
-		serde_json::to_vec(&self.data).unwrap_or_default()
+	fn data(self) -> Result<Vec<u8>, Error> {
+		serde_json::to_vec(&self.data).map_err(Error::custom)
 	}
-	fn modify_relation(mut self, _from: &Index, to: &Index) -> Self
+	fn modify_relation(mut self, _from: &Index, to: &Index) -> Result<Self, Error>
 	where
 		Self: Sized,
 	{
 		self.data.import = Some(from_index(to));
-		self
+		Ok(self)
 	}
 	fn merge(self, other: Self) -> Result<Self, Error>
 	where
@@ -145,14 +144,14 @@ impl superfusion::prelude::File for Text {
 	fn relation(&self) -> Vec<Relation> {
 		vec![]
 	}
-	fn data(self) -> Vec<u8> {
-		self.data.as_bytes().to_vec()
+	fn data(self) -> Result<Vec<u8>, Error> {
+		Ok(self.data.into_bytes())
 	}
-	fn modify_relation(self, _from: &Index, _to: &Index) -> Self
+	fn modify_relation(self, _from: &Index, _to: &Index) -> Result<Self, Error>
 	where
 		Self: Sized,
 	{
-		self
+		Ok(self)
 	}
 	fn merge(self, other: Self) -> Result<Self, Error>
 	where
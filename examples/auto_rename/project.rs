@@ -36,7 +36,7 @@ impl superfusion::project::Project for Project {
 	fn pid(&self) -> Pid {
 		self.pid
 	}
-	fn indexes(&self) -> IndexList {
+	fn indexes(&self) -> IndexList<'_> {
 		self.indexes.iter().collect()
 	}
 }
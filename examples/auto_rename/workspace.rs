@@ -3,7 +3,8 @@ use super::project::Project;
 use anyhow::Result;
 use glob::Pattern;
 use lazy_static::lazy_static;
-use std::path::{Path, PathBuf};
+use std::io::Read;
+use std::path::PathBuf;
 use superfusion::prelude::Pid;
 use superfusion::prelude::*;
 
@@ -20,12 +21,10 @@ pub struct Workspace {
 impl Workspace {
 	pub fn from_directory(root: impl Into<PathBuf>) -> Result<Self> {
 		let root = root.into();
-		let projects = root
-			.read_dir()?
-			.filter_map(workspace_entry)
-			.enumerate()
-			.map(create_project)
-			.collect();
+		let mut paths: Vec<_> = root.read_dir()?.filter_map(workspace_entry).collect();
+		paths.sort();
+
+		let projects = paths.into_iter().enumerate().map(create_project).collect();
 
 		let result = Self { projects };
 		Ok(result)
@@ -51,8 +50,8 @@ impl superfusion::workspace::Workspace for Workspace {
 		&self.projects
 	}
 
-	fn file(path: &Path, pid: Pid) -> Option<Self::File> {
-		Asset::new(path, pid).ok()
+	fn file(index: &Index, reader: &mut dyn Read) -> Result<Self::File, Error> {
+		Asset::new(index, reader).map_err(Error::custom)
 	}
 
 	fn strategy(&self, index: &Index) -> Strategy {
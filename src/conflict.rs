@@ -0,0 +1,103 @@
+use super::prelude::{Index, Pid, Strategy};
+use std::iter::FromIterator;
+use std::path::{Path, PathBuf};
+use std::slice::Iter;
+
+/// Indexes from different projects that share the same path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+	path: PathBuf,
+	indexes: Vec<Index>,
+	strategy: Strategy,
+}
+
+impl Conflict {
+	pub fn new(path: impl Into<PathBuf>, indexes: Vec<Index>, strategy: Strategy) -> Self {
+		let path = path.into();
+		Self {
+			path,
+			indexes,
+			strategy,
+		}
+	}
+
+	/// Relative path that every index share
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Conflicting indexes, from the lowest priority project to the highest
+	pub fn indexes(&self) -> &[Index] {
+		&self.indexes
+	}
+
+	/// Pid of every project involved in this conflict
+	pub fn pids(&self) -> impl Iterator<Item = &Pid> {
+		self.indexes.iter().map(Index::pid)
+	}
+
+	/// Strategy that was chosen to handle this conflict
+	pub fn strategy(&self) -> Strategy {
+		self.strategy
+	}
+}
+
+/// Every conflict that was found while resolving the workspace, sorted by path.
+///
+/// ```
+/// # use superfusion::conflict::{Conflict, ConflictReport};
+/// # use superfusion::prelude::{Index, Pid, Strategy};
+/// let indexes = vec![Index::new(Pid::new(0), "foo.json"), Index::new(Pid::new(1), "foo.json")];
+/// let report: ConflictReport = vec![Conflict::new("foo.json", indexes, Strategy::Rename)].into_iter().collect();
+///
+/// assert_eq!(report.get("foo.json").map(Conflict::strategy), Some(Strategy::Rename));
+/// assert!(report.get("bar.json").is_none());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConflictReport {
+	conflicts: Vec<Conflict>,
+}
+
+impl ConflictReport {
+	pub fn new(mut conflicts: Vec<Conflict>) -> Self {
+		conflicts.sort_by(|a, b| a.path.cmp(&b.path));
+		Self { conflicts }
+	}
+
+	/// Get the conflict at the given relative `path`
+	pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&Conflict> {
+		let path = path.as_ref();
+		self.conflicts
+			.binary_search_by(|conflict| conflict.path().cmp(path))
+			.ok()
+			.map(|i| &self.conflicts[i])
+	}
+
+	pub fn iter(&self) -> Iter<'_, Conflict> {
+		self.conflicts.iter()
+	}
+
+	pub fn len(&self) -> usize {
+		self.conflicts.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.conflicts.is_empty()
+	}
+}
+
+impl<'a> IntoIterator for &'a ConflictReport {
+	type Item = &'a Conflict;
+	type IntoIter = Iter<'a, Conflict>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.conflicts.iter()
+	}
+}
+
+impl FromIterator<Conflict> for ConflictReport {
+	fn from_iter<T: IntoIterator<Item = Conflict>>(iter: T) -> Self {
+		let conflicts = iter.into_iter().collect();
+		Self::new(conflicts)
+	}
+}
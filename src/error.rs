@@ -13,6 +13,18 @@ pub enum Error {
 		source: std::io::Error,
 	},
 
+	/// Failed to read file from the given path
+	#[error("Unable to read data from {path}")]
+	Read {
+		path: PathBuf,
+		#[source]
+		source: std::io::Error,
+	},
+
+	/// The given path does not exist in the output
+	#[error("Unable to find {path}")]
+	NotFound { path: PathBuf },
+
 	/// Failed to create parent directory of the given path
 	#[error("Unable to get the parent of this path {path}")]
 	Parent { path: PathBuf },
@@ -29,6 +41,75 @@ pub enum Error {
 		source: std::io::Error,
 	},
 
+	/// Failed to move a file or directory to another location
+	#[error("Unable to move {from} to {to}")]
+	Rename {
+		from: PathBuf,
+		to: PathBuf,
+		#[source]
+		source: std::io::Error,
+	},
+
+	/// Failed to remove the given directory
+	#[error("Unable to remove directory {path}")]
+	RemoveDir {
+		path: PathBuf,
+		#[source]
+		source: std::io::Error,
+	},
+
+	/// Failed to read or write a zip archive, only returned with the `zip` feature
+	#[error("Unable to access zip archive")]
+	Archive {
+		#[source]
+		source: Box<dyn std::error::Error + Sync + Send>,
+	},
+
+	/// The given path would lead outside of the directory or archive it is relative to
+	#[error("Refuse to access {path} outside of its root")]
+	UnsafePath { path: PathBuf },
+
+	/// Failed to find an unused path for the renamed index
+	#[error("Unable to find an unused name for {index}, last attempt was {path}")]
+	RenameCollision { index: Index, path: PathBuf },
+
+	/// Multiple projects contain the same path and its strategy does not allow it
+	#[error("Conflicting files at {path} are not allowed, found in {indexes:?}")]
+	Conflict { path: PathBuf, indexes: Vec<Index> },
+
+	/// Failed to parse a line of the saved conflict decisions
+	#[error("Unable to parse decision `{line}` in {path}")]
+	InvalidDecision { path: PathBuf, line: String },
+
+	/// Failed to update a relation of a file after the related index was renamed
+	#[error("Unable to modify relation from {from} to {to}")]
+	ModifyRelation {
+		from: Index,
+		to: Index,
+		#[source]
+		source: Box<Error>,
+	},
+
+	/// Failed to open the given file from its project or to load it with [Workspace::file()](../workspace/trait.Workspace.html#tymethod.file)
+	#[error("Unable to load {index}")]
+	Load {
+		index: Index,
+		#[source]
+		source: Box<Error>,
+	},
+
+	/// Failed to prepare the given file to be written into the output
+	#[error("Unable to export {index}")]
+	File {
+		index: Index,
+		#[source]
+		source: Box<Error>,
+	},
+
+	/// [File::merge_all()](../file/trait.File.html#method.merge_all) was called without any file
+	#[error("Unable to merge an empty list of files")]
+	NothingToMerge,
+
 	#[error("Unknown index: {index}")]
 	UnknownIndex { index: Index },
 
@@ -41,6 +122,53 @@ impl Error {
 		Error::UnknownIndex { index }
 	}
 
+	pub fn rename_collision(index: Index, path: impl Into<PathBuf>) -> Self {
+		Error::RenameCollision {
+			index,
+			path: path.into(),
+		}
+	}
+
+	pub fn conflict(path: impl Into<PathBuf>, indexes: Vec<Index>) -> Self {
+		Error::Conflict {
+			path: path.into(),
+			indexes,
+		}
+	}
+
+	pub fn invalid_decision(path: impl Into<PathBuf>, line: impl Into<String>) -> Self {
+		Error::InvalidDecision {
+			path: path.into(),
+			line: line.into(),
+		}
+	}
+
+	pub fn modify_relation(from: Index, to: Index, source: Error) -> Self {
+		Error::ModifyRelation {
+			from,
+			to,
+			source: Box::new(source),
+		}
+	}
+
+	pub fn load(index: Index, source: Error) -> Self {
+		Error::Load {
+			index,
+			source: Box::new(source),
+		}
+	}
+
+	pub fn file(index: Index, source: Error) -> Self {
+		Error::File {
+			index,
+			source: Box::new(source),
+		}
+	}
+
+	pub fn nothing_to_merge() -> Self {
+		Error::NothingToMerge
+	}
+
 	pub fn write(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
 		Error::Write {
 			path: path.into(),
@@ -48,6 +176,17 @@ impl Error {
 		}
 	}
 
+	pub fn read(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+		Error::Read {
+			path: path.into(),
+			source,
+		}
+	}
+
+	pub fn not_found(path: impl Into<PathBuf>) -> Self {
+		Error::NotFound { path: path.into() }
+	}
+
 	pub fn parent(path: impl Into<PathBuf>) -> Self {
 		Error::Parent { path: path.into() }
 	}
@@ -56,6 +195,10 @@ impl Error {
 		Error::NoFileName { path: path.into() }
 	}
 
+	pub fn unsafe_path(path: impl Into<PathBuf>) -> Self {
+		Error::UnsafePath { path: path.into() }
+	}
+
 	pub fn dir_all(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
 		Error::CreateDirAll {
 			path: path.into(),
@@ -63,6 +206,28 @@ impl Error {
 		}
 	}
 
+	pub fn rename(from: impl Into<PathBuf>, to: impl Into<PathBuf>, source: std::io::Error) -> Self {
+		Error::Rename {
+			from: from.into(),
+			to: to.into(),
+			source,
+		}
+	}
+
+	pub fn remove_dir(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+		Error::RemoveDir {
+			path: path.into(),
+			source,
+		}
+	}
+
+	#[cfg(feature = "zip")]
+	pub fn archive(source: zip::result::ZipError) -> Self {
+		Error::Archive {
+			source: Box::new(source),
+		}
+	}
+
 	pub fn custom(error: impl std::error::Error + Send + Sync + 'static) -> Self {
 		let error = Box::new(error);
 		Error::Custom(error)
@@ -14,11 +14,13 @@ pub trait File {
 	///
 	/// Note that the file's data *should* already be stored inside this struct and this method simply return that data.  
 	/// This is to allow transforming relationship via [modify_relation()](#method.modify_relation) method without rewriting the file.
-	fn data(self) -> Vec<u8>;
+	///
+	/// Returning an error (e.g. when the file can not be serialized) abort the export of this file instead of writing an empty file.
+	fn data(self) -> Result<Vec<u8>, Error>;
 
 	/// Get call when an Index that have relation to this file get renamed.  
 	/// This method should act appropriately and rename the reference to that index within this file as well.
-	fn modify_relation(self, from: &Index, to: &Index) -> Self
+	fn modify_relation(self, from: &Index, to: &Index) -> Result<Self, Error>
 	where
 		Self: Sized;
 
@@ -31,6 +33,21 @@ pub trait File {
 	{
 		Ok(other)
 	}
+
+	/// Merge every conflicting copy of a file at once.
+	///
+	/// `files` is ordered from the lowest priority project to the highest and is never empty during an export.
+	/// Override this for formats that can not be merged correctly one pair at a time.
+	///
+	/// By default, this fold over [merge()](#method.merge) from the lowest priority copy to the highest.
+	fn merge_all(files: Vec<Self>) -> Result<Self, Error>
+	where
+		Self: Sized,
+	{
+		let mut files = files.into_iter();
+		let first = files.next().ok_or_else(Error::nothing_to_merge)?;
+		files.try_fold(first, Self::merge)
+	}
 }
 
 /// Describing a relationship to another file.
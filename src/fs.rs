@@ -1,11 +1,61 @@
 use super::Error;
-use std::path::Path;
+use log::*;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 
 type Result<T> = std::result::Result<T, Error>;
 
 pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<()> {
 	let path = path.as_ref();
-	std::fs::write(&path, contents).map_err(|io| Error::write(path, io))
+	std::fs::write(path, contents).map_err(|io| Error::write(path, io))
+}
+
+pub fn open<P: AsRef<Path>>(path: P) -> Result<std::fs::File> {
+	let path = path.as_ref();
+	std::fs::File::open(path).map_err(|io| Error::read(path, io))
+}
+
+pub fn create<P: AsRef<Path>>(path: P) -> Result<std::fs::File> {
+	let path = path.as_ref();
+	prepare_parent(path)?;
+	std::fs::File::create(path).map_err(|io| Error::write(path, io))
+}
+
+/// Stream everything from `reader` into a new file at the given `path`
+pub fn copy_from<P: AsRef<Path>>(path: P, reader: &mut dyn Read) -> Result<()> {
+	let path = path.as_ref();
+	let mut file = create(path)?;
+	std::io::copy(reader, &mut file)
+		.map(|_| ())
+		.map_err(|io| Error::write(path, io))
+}
+
+/// Remove the given file if it exists
+pub fn remove_file(path: &Path) -> Result<()> {
+	match std::fs::remove_file(path) {
+		Err(io) if io.kind() != std::io::ErrorKind::NotFound => Err(Error::write(path, io)),
+		_ => Ok(()),
+	}
+}
+
+/// Return the path of every file inside `root`, relative to `root`
+pub fn walk(root: &Path) -> Result<Vec<PathBuf>> {
+	let mut files = Vec::new();
+	let mut pending = vec![root.to_path_buf()];
+
+	while let Some(dir) = pending.pop() {
+		let entries = std::fs::read_dir(&dir).map_err(|io| Error::read(&dir, io))?;
+		for entry in entries {
+			let path = entry.map_err(|io| Error::read(&dir, io))?.path();
+			if path.is_dir() {
+				pending.push(path);
+			} else if let Ok(relative) = path.strip_prefix(root) {
+				files.push(relative.to_path_buf());
+			}
+		}
+	}
+
+	Ok(files)
 }
 
 pub fn parent(path: &Path) -> Result<&Path> {
@@ -18,6 +68,22 @@ pub fn file_stem(path: &Path) -> Result<&str> {
 		.ok_or_else(|| Error::filename(path))
 }
 
+pub fn file_name(path: &Path) -> Result<&str> {
+	path.file_name()
+		.and_then(|s| s.to_str())
+		.ok_or_else(|| Error::filename(path))
+}
+
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+	let from = from.as_ref();
+	let to = to.as_ref();
+	std::fs::rename(from, to).map_err(|io| Error::rename(from, to, io))
+}
+
+pub fn remove_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
+	std::fs::remove_dir_all(&path).map_err(|io| Error::remove_dir(path.as_ref(), io))
+}
+
 pub fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
 	std::fs::create_dir_all(&path).map_err(|io| Error::dir_all(path.as_ref(), io))
 }
@@ -31,3 +97,85 @@ pub fn prepare_parent(path: &Path) -> Result<()> {
 
 	Ok(())
 }
+
+/// Remove the given directory if it exists
+pub fn clear_dir(path: &Path) -> Result<()> {
+	if path.exists() {
+		remove_dir_all(path)?;
+	}
+
+	Ok(())
+}
+
+/// Check that the relative `path` can not lead outside of the directory it is relative to
+pub fn relative(path: &Path) -> Result<&Path> {
+	let escaping = path.components().any(|component| {
+		matches!(component, Component::ParentDir | Component::RootDir | Component::Prefix(_))
+	});
+
+	if escaping {
+		Err(Error::unsafe_path(path))
+	} else {
+		Ok(path)
+	}
+}
+
+/// Resolve a `path` that does not end with a file name, such as `.` or `out/..`, into the directory it point at.
+///
+/// Other paths are returned untouched, resolving only fail when the directory does not exist.
+pub fn resolve(path: &Path) -> Result<PathBuf> {
+	if path.file_name().is_some() {
+		return Ok(path.to_path_buf());
+	}
+
+	std::fs::canonicalize(path).map_err(|io| Error::read(path, io))
+}
+
+/// Return a hidden sibling of `path` with the given `suffix` appended to its name.
+///
+/// This is where the exporter write into before moving the result into place.
+pub fn sibling(path: &Path, suffix: &str) -> Result<PathBuf> {
+	let name = file_name(path)?;
+	let parent = parent(path)?;
+	Ok(parent.join(format!(".{}.{}", name, suffix)))
+}
+
+/// Move the `staging` directory into `target`, replacing whatever was there before.
+///
+/// The previous `target` is only removed after `staging` was moved into place and restored if that fail.
+pub fn replace_dir(staging: &Path, target: &Path) -> Result<()> {
+	if !target.exists() {
+		return rename(staging, target);
+	}
+
+	let backup = sibling(target, "backup")?;
+	clear_dir(&backup)?;
+	rename(target, &backup)?;
+
+	if let Err(error) = rename(staging, target) {
+		rename(&backup, target)?;
+		return Err(error);
+	}
+
+	if let Err(error) = remove_dir_all(&backup) {
+		warn!("Unable to remove previous output {}: {}", backup.display(), error);
+	}
+
+	Ok(())
+}
+
+/// Convert a relative path into the name of a zip entry.
+///
+/// Zip entries always use `/` as separator regardless of the platform.
+#[cfg(feature = "zip")]
+pub fn entry_name(path: &Path) -> Result<String> {
+	let components: Option<Vec<_>> = relative(path)?
+		.components()
+		.filter(|component| component.as_os_str() != ".")
+		.map(|component| component.as_os_str().to_str())
+		.collect();
+
+	components
+		.map(|components| components.join("/"))
+		.ok_or_else(|| Error::filename(path))
+}
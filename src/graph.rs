@@ -0,0 +1,324 @@
+use super::prelude::Index;
+use std::collections::{HashMap, VecDeque};
+
+/// A relation pointing at an index that does not exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingRelation {
+	from: Index,
+	to: Index,
+}
+
+impl DanglingRelation {
+	pub fn new(from: Index, to: Index) -> Self {
+		Self { from, to }
+	}
+
+	/// Index of the file that contain the relation
+	pub fn from(&self) -> &Index {
+		&self.from
+	}
+
+	/// Index that the relation point at
+	pub fn to(&self) -> &Index {
+		&self.to
+	}
+}
+
+/// Graph of every [Relation](../file/struct.Relation.html) between files in the workspace.
+///
+/// An edge go from a file to every index it reference.
+/// Nodes are kept in the order they were inserted so every query return its result in a stable order.
+///
+/// ```
+/// # use superfusion::graph::RelationGraph;
+/// # use superfusion::prelude::{Index, Pid};
+/// let foo = Index::new(Pid::new(0), "foo.json");
+/// let bar = Index::new(Pid::new(0), "bar.json");
+/// let baz = Index::new(Pid::new(0), "baz.json");
+///
+/// let mut graph = RelationGraph::new();
+/// graph.insert(foo.clone(), bar.clone());
+/// graph.insert(bar.clone(), baz.clone());
+///
+/// assert_eq!(graph.dependents(&bar), vec![&foo]);
+/// assert_eq!(graph.transitive_dependencies(&foo), vec![&bar, &baz]);
+/// assert!(!graph.has_cycle());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct RelationGraph {
+	nodes: Vec<Index>,
+	ids: HashMap<Index, usize>,
+	dependencies: Vec<Vec<usize>>,
+	dependents: Vec<Vec<usize>>,
+}
+
+impl RelationGraph {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Insert a node without any relation, does nothing if the node already exists
+	pub fn add(&mut self, index: Index) {
+		self.id_or_insert(index);
+	}
+
+	/// Insert a relation where `from` reference `to`
+	pub fn insert(&mut self, from: Index, to: Index) {
+		let from = self.id_or_insert(from);
+		let to = self.id_or_insert(to);
+
+		if !self.dependencies[from].contains(&to) {
+			self.dependencies[from].push(to);
+			self.dependents[to].push(from);
+		}
+	}
+
+	fn id_or_insert(&mut self, index: Index) -> usize {
+		if let Some(&id) = self.ids.get(&index) {
+			return id;
+		}
+
+		let id = self.nodes.len();
+		self.ids.insert(index.clone(), id);
+		self.nodes.push(index);
+		self.dependencies.push(Vec::new());
+		self.dependents.push(Vec::new());
+		id
+	}
+
+	/// Check if the given index is part of this graph
+	pub fn contains(&self, index: &Index) -> bool {
+		self.ids.contains_key(index)
+	}
+
+	/// Every index in this graph, in insertion order
+	pub fn nodes(&self) -> &[Index] {
+		&self.nodes
+	}
+
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+
+	/// Indexes that are directly referenced by the given index
+	pub fn dependencies(&self, index: &Index) -> Vec<&Index> {
+		self.neighbours(index, &self.dependencies)
+	}
+
+	/// Indexes that directly reference the given index
+	pub fn dependents(&self, index: &Index) -> Vec<&Index> {
+		self.neighbours(index, &self.dependents)
+	}
+
+	/// Every index that can be reached from the given index by following its relations
+	pub fn transitive_dependencies(&self, index: &Index) -> Vec<&Index> {
+		self.reachable(index, &self.dependencies)
+	}
+
+	/// Every index that would be affected by modifying the given index, directly or not
+	pub fn transitive_dependents(&self, index: &Index) -> Vec<&Index> {
+		self.reachable(index, &self.dependents)
+	}
+
+	fn neighbours(&self, index: &Index, edges: &[Vec<usize>]) -> Vec<&Index> {
+		self.ids
+			.get(index)
+			.map(|&id| edges[id].iter().map(|&i| &self.nodes[i]).collect())
+			.unwrap_or_default()
+	}
+
+	/// Breadth-first search from `index`, the starting index is only included if it can reach itself
+	fn reachable(&self, index: &Index, edges: &[Vec<usize>]) -> Vec<&Index> {
+		let start = match self.ids.get(index) {
+			Some(&id) => id,
+			None => return Vec::new(),
+		};
+
+		let mut visited = vec![false; self.nodes.len()];
+		let mut queue: VecDeque<_> = edges[start].iter().copied().collect();
+		let mut result = Vec::new();
+
+		while let Some(id) = queue.pop_front() {
+			if visited[id] {
+				continue;
+			}
+
+			visited[id] = true;
+			result.push(&self.nodes[id]);
+			queue.extend(edges[id].iter().filter(|&&next| !visited[next]));
+		}
+
+		result
+	}
+
+	/// Every relation whose target does not satisfy `exists`.
+	///
+	/// Relations are listed in insertion order of their target.
+	pub fn dangling<F>(&self, exists: F) -> Vec<DanglingRelation>
+	where
+		F: Fn(&Index) -> bool,
+	{
+		self.nodes
+			.iter()
+			.enumerate()
+			.filter(|(_, index)| !exists(index))
+			.flat_map(|(id, to)| {
+				self.dependents[id]
+					.iter()
+					.map(move |&from| DanglingRelation::new(self.nodes[from].clone(), to.clone()))
+			})
+			.collect()
+	}
+
+	/// Check if any file reference itself, directly or not
+	pub fn has_cycle(&self) -> bool {
+		!self.cycles().is_empty()
+	}
+
+	/// Every group of indexes that reference each other in a cycle.
+	///
+	/// Each cycle is a strongly connected component of the graph, listed in insertion order.
+	pub fn cycles(&self) -> Vec<Vec<&Index>> {
+		self.components()
+			.into_iter()
+			.filter(|component| {
+				let id = component[0];
+				component.len() > 1 || self.dependencies[id].contains(&id)
+			})
+			.map(|component| component.into_iter().map(|id| &self.nodes[id]).collect())
+			.collect()
+	}
+
+	/// Tarjan's strongly connected components algorithm without recursion
+	fn components(&self) -> Vec<Vec<usize>> {
+		let count = self.nodes.len();
+		let mut order: Vec<Option<usize>> = vec![None; count];
+		let mut low = vec![0; count];
+		let mut on_stack = vec![false; count];
+		let mut stack = Vec::new();
+		let mut counter = 0;
+		let mut components = Vec::new();
+
+		for start in 0..count {
+			if order[start].is_some() {
+				continue;
+			}
+
+			let mut calls = vec![(start, 0)];
+			order[start] = Some(counter);
+			low[start] = counter;
+			counter += 1;
+			stack.push(start);
+			on_stack[start] = true;
+
+			while let Some(&(node, edge)) = calls.last() {
+				if let Some(&next) = self.dependencies[node].get(edge) {
+					if let Some(call) = calls.last_mut() {
+						call.1 += 1;
+					}
+
+					match order[next] {
+						None => {
+							order[next] = Some(counter);
+							low[next] = counter;
+							counter += 1;
+							stack.push(next);
+							on_stack[next] = true;
+							calls.push((next, 0));
+						}
+						Some(visited) if on_stack[next] => low[node] = low[node].min(visited),
+						Some(_) => {}
+					}
+					continue;
+				}
+
+				calls.pop();
+				if let Some(&(parent, _)) = calls.last() {
+					low[parent] = low[parent].min(low[node]);
+				}
+
+				if Some(low[node]) == order[node] {
+					let mut component = Vec::new();
+					while let Some(member) = stack.pop() {
+						on_stack[member] = false;
+						component.push(member);
+						if member == node {
+							break;
+						}
+					}
+					component.sort_unstable();
+					components.push(component);
+				}
+			}
+		}
+
+		components.sort_by_key(|component| component[0]);
+		components
+	}
+}
+
+#[cfg(test)]
+#[allow(clippy::disallowed_names)]
+mod tests {
+	use super::*;
+	use crate::prelude::Pid;
+
+	fn index(path: &str) -> Index {
+		Index::new(Pid::new(0), path)
+	}
+
+	#[test]
+	fn dependents_of_index() {
+		let mut graph = RelationGraph::new();
+		graph.insert(index("foo"), index("baz"));
+		graph.insert(index("bar"), index("baz"));
+
+		assert_eq!(
+			graph.dependents(&index("baz")),
+			vec![&index("foo"), &index("bar")]
+		);
+		assert!(graph.dependencies(&index("baz")).is_empty());
+	}
+
+	#[test]
+	fn transitive_dependents_of_index() {
+		let mut graph = RelationGraph::new();
+		graph.insert(index("foo"), index("bar"));
+		graph.insert(index("bar"), index("baz"));
+		graph.add(index("qux"));
+
+		let result = graph.transitive_dependents(&index("baz"));
+		assert_eq!(result, vec![&index("bar"), &index("foo")]);
+	}
+
+	#[test]
+	fn detect_cycle() {
+		let mut graph = RelationGraph::new();
+		graph.insert(index("foo"), index("bar"));
+		graph.insert(index("bar"), index("baz"));
+		graph.insert(index("baz"), index("foo"));
+		graph.insert(index("qux"), index("foo"));
+
+		let cycles = graph.cycles();
+		assert_eq!(
+			cycles,
+			vec![vec![&index("foo"), &index("bar"), &index("baz")]]
+		);
+		assert!(graph
+			.transitive_dependencies(&index("foo"))
+			.contains(&&index("foo")));
+	}
+
+	#[test]
+	fn detect_self_reference() {
+		let mut graph = RelationGraph::new();
+		graph.insert(index("foo"), index("foo"));
+		graph.insert(index("bar"), index("foo"));
+
+		assert_eq!(graph.cycles(), vec![vec![&index("foo")]]);
+	}
+}
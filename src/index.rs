@@ -45,6 +45,8 @@ impl Index {
 	/// This method will attempt to create a file name that is unique across the entire workspace.
 	/// It's usually called when workspace found a conflicting file.
 	///
+	/// The result is not checked against other indexes, [Timeline](../timeline/struct.Timeline.html) will call this method again on the result if the new path is already taken.
+	///
 	/// # Note
 	/// 1. `format` is a 'formatter function', this function must return a unique formatted string base on its `Pid` and filename.
 	/// 2. Changing file extension is not permitted.
@@ -167,7 +169,7 @@ impl<'a> IndexList<'a> {
 	}
 
 	/// Create iterator over Index's reference
-	pub fn iter(&self) -> Iter<&Index> {
+	pub fn iter(&self) -> Iter<'_, &Index> {
 		self.indexes.iter()
 	}
 }
@@ -215,16 +217,18 @@ impl<'a> IndexMapping<'a> {
 	}
 
 	/// Apply mapping to the given `file`'s relationship
-	pub fn apply_mapping<F: File>(&self, file: F) -> F {
+	pub fn apply_mapping<F: File>(&self, file: F) -> Result<F, Error> {
 		let modify_if_exists = |acc: F, ref from| match self.get(from) {
-			Some(to) => acc.modify_relation(from, to),
-			None => acc,
+			Some(to) => acc
+				.modify_relation(from, to)
+				.map_err(|error| Error::modify_relation(from.clone(), to.clone(), error)),
+			None => Ok(acc),
 		};
 
 		file.relation()
 			.into_iter()
 			.map(Relation::index)
-			.fold(file, modify_if_exists)
+			.try_fold(file, modify_if_exists)
 	}
 }
 
@@ -236,7 +240,7 @@ impl<'a> FromIterator<(&'a Index, Index)> for IndexMapping<'a> {
 }
 
 #[cfg(test)]
-#[allow(clippy::blacklisted_name)]
+#[allow(clippy::disallowed_names)]
 mod tests {
 	use super::*;
 
@@ -1,25 +1,50 @@
+/// Conflicts found while resolving the workspace
+pub mod conflict;
 /// Specify criteria that can be considered a 'project'
 pub mod criteria;
 /// File interface
 pub mod file;
+/// Dependency graph between files
+pub mod graph;
 /// Internal representation of a single file inside the project
 pub mod index;
+/// Dry-run description of an export
+pub mod plan;
 /// Project interface
 pub mod project;
+/// Record of where every exported file came from
+pub mod provenance;
+/// Failures collected by a lenient export
+pub mod report;
+/// Interactive conflict resolution
+pub mod resolver;
+/// Output destination interface
+pub mod sink;
+/// Virtual filesystem interface
+pub mod source;
 pub mod timeline;
 /// Workspace interface
 pub mod workspace;
 
 mod error;
+/// Hashes recorded by incremental export
+mod manifest;
 /// Modified `std::fs` module that contain a little more context in Error.
 mod fs;
 pub use error::Error;
 
 /// Re-export necessary data
 pub mod prelude {
+	pub use crate::conflict::{Conflict, ConflictReport};
 	pub use crate::file::{File, Relation};
 	pub use crate::index::{Index, IndexList, IndexMapping};
+	pub use crate::plan::{ExportPlan, PlanEntry};
 	pub use crate::project::{Pid, Project, Strategy};
+	pub use crate::provenance::Provenance;
+	pub use crate::report::ExportReport;
+	pub use crate::resolver::ConflictResolver;
+	pub use crate::sink::OutputSink;
+	pub use crate::source::Source;
 	pub use crate::timeline::Timeline;
 	pub use crate::workspace::Workspace;
 	pub use crate::Error;
@@ -0,0 +1,109 @@
+use super::fs;
+use super::prelude::Error;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+const HEADER: &str = "superfusion-manifest 1";
+
+/// Record of a previous incremental export.
+///
+/// For every output file, this store a hash of everything that contributed to it and a hash of the written content.
+/// An output only need to be exported again when one of those hashes change.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Manifest {
+	mapping: u64,
+	outputs: BTreeMap<PathBuf, Record>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Record {
+	pub input: u64,
+	pub output: u64,
+}
+
+impl Manifest {
+	pub fn new(mapping: u64) -> Self {
+		Self {
+			mapping,
+			outputs: BTreeMap::new(),
+		}
+	}
+
+	/// Load the manifest at the given `path`.
+	///
+	/// Missing or unreadable manifest is treated as empty which cause everything to be exported again.
+	pub fn load(path: &Path) -> Self {
+		std::fs::read_to_string(path)
+			.ok()
+			.and_then(|content| Self::parse(&content))
+			.unwrap_or_default()
+	}
+
+	fn parse(content: &str) -> Option<Self> {
+		let mut lines = content.lines();
+		if lines.next()? != HEADER {
+			return None;
+		}
+
+		let mapping = lines.next()?.strip_prefix("mapping ")?;
+		let mut manifest = Self::new(parse_hash(mapping)?);
+
+		for line in lines {
+			let mut parts = line.splitn(3, ' ');
+			let input = parse_hash(parts.next()?)?;
+			let output = parse_hash(parts.next()?)?;
+			let path = PathBuf::from(parts.next()?);
+			manifest.insert(path, Record { input, output });
+		}
+
+		Some(manifest)
+	}
+
+	pub fn save(&self, path: &Path) -> Result<(), Error> {
+		let mut content = format!("{}\nmapping {:016x}\n", HEADER, self.mapping);
+		for (file, record) in &self.outputs {
+			let line = format!(
+				"{:016x} {:016x} {}\n",
+				record.input,
+				record.output,
+				file.display()
+			);
+			content.push_str(&line);
+		}
+
+		fs::prepare_parent(path)?;
+		fs::write(path, content)
+	}
+
+	pub fn mapping(&self) -> u64 {
+		self.mapping
+	}
+
+	pub fn get(&self, path: &Path) -> Option<&Record> {
+		self.outputs.get(path)
+	}
+
+	pub fn insert(&mut self, path: PathBuf, record: Record) {
+		self.outputs.insert(path, record);
+	}
+
+	pub fn paths(&self) -> impl Iterator<Item = &Path> {
+		self.outputs.keys().map(PathBuf::as_path)
+	}
+}
+
+fn parse_hash(value: &str) -> Option<u64> {
+	u64::from_str_radix(value, 16).ok()
+}
+
+/// Hash the given value.
+///
+/// The hash is only used to detect changes between two runs so it does not need to be stable across Rust versions,
+/// a different hash simply cause the output to be exported again.
+pub(crate) fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	value.hash(&mut hasher);
+	hasher.finish()
+}
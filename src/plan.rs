@@ -0,0 +1,160 @@
+use super::prelude::{Index, IndexMapping, Strategy};
+use std::collections::BTreeMap;
+use std::iter::FromIterator;
+use std::path::Path;
+use std::slice::Iter;
+
+/// A single step of the export, describing where a source index will end up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanEntry {
+	source: Index,
+	strategy: Strategy,
+	output: Index,
+}
+
+impl PlanEntry {
+	pub fn new(source: Index, strategy: Strategy, output: Index) -> Self {
+		Self {
+			source,
+			strategy,
+			output,
+		}
+	}
+
+	/// Index of the file inside its original project
+	pub fn source(&self) -> &Index {
+		&self.source
+	}
+
+	/// Strategy that was chosen for this index
+	pub fn strategy(&self) -> Strategy {
+		self.strategy
+	}
+
+	/// Index of the file inside the output project
+	pub fn output(&self) -> &Index {
+		&self.output
+	}
+}
+
+/// A dry-run of the export.
+///
+/// This describe everything [Timeline::export_to()](../timeline/struct.Timeline.html#method.export_to) is going to do without touching the filesystem.
+/// Entries are listed in the same order they will be exported.
+/// Source indexes that are left out of the export are kept aside in [dropped()](#method.dropped).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExportPlan {
+	entries: Vec<PlanEntry>,
+	dropped: Vec<(Index, Strategy)>,
+}
+
+impl ExportPlan {
+	pub fn new(entries: Vec<PlanEntry>) -> Self {
+		Self::with_dropped(entries, Vec::new())
+	}
+
+	pub fn with_dropped(entries: Vec<PlanEntry>, dropped: Vec<(Index, Strategy)>) -> Self {
+		Self { entries, dropped }
+	}
+
+	pub fn entries(&self) -> &[PlanEntry] {
+		&self.entries
+	}
+
+	/// Source indexes that will not be exported along with the strategy that left them out
+	pub fn dropped(&self) -> &[(Index, Strategy)] {
+		&self.dropped
+	}
+
+	pub fn iter(&self) -> Iter<'_, PlanEntry> {
+		self.entries.iter()
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Group every source index by the output index it will be written to.
+	///
+	/// Groups are sorted by the path of their output index.
+	pub fn groups(&self) -> Vec<(&Index, Vec<&Index>)> {
+		self.by_output()
+			.into_iter()
+			.map(|(_, (output, entries))| {
+				let sources = entries.into_iter().map(PlanEntry::source).collect();
+				(output, sources)
+			})
+			.collect()
+	}
+
+	/// Output indexes that will be created by merging multiple source files together.
+	///
+	/// ```
+	/// # use superfusion::plan::{ExportPlan, PlanEntry};
+	/// # use superfusion::prelude::{Index, Pid, Strategy};
+	/// let output = Index::new(Pid::new(2), "data/tick.txt");
+	/// let plan = ExportPlan::new(vec![
+	///     PlanEntry::new(Index::new(Pid::new(0), "data/tick.txt"), Strategy::Merge, output.clone()),
+	///     PlanEntry::new(Index::new(Pid::new(1), "data/tick.txt"), Strategy::Merge, output.clone()),
+	///     PlanEntry::new(Index::new(Pid::new(0), "pack.mcmeta"), Strategy::Replace, Index::new(Pid::new(2), "pack.mcmeta")),
+	/// ]);
+	///
+	/// let merges = plan.merges();
+	/// assert_eq!(merges.len(), 1);
+	/// assert_eq!(merges[0].0, &output);
+	/// assert_eq!(merges[0].1.len(), 2);
+	/// ```
+	pub fn merges(&self) -> Vec<(&Index, Vec<&Index>)> {
+		self.by_output()
+			.into_iter()
+			.filter(|(_, (_, entries))| {
+				entries.len() > 1 && entries.iter().all(|entry| entry.strategy() == Strategy::Merge)
+			})
+			.map(|(_, (output, entries))| {
+				let sources = entries.into_iter().map(PlanEntry::source).collect();
+				(output, sources)
+			})
+			.collect()
+	}
+
+	/// Mapping from every source index to its output index
+	pub fn mapping(&self) -> IndexMapping<'_> {
+		self.iter()
+			.map(|entry| (entry.source(), entry.output().clone()))
+			.collect()
+	}
+
+	/// Every entry keyed by the path of its output index, all outputs share the same pid
+	fn by_output(&self) -> BTreeMap<&Path, (&Index, Vec<&PlanEntry>)> {
+		let mut groups: BTreeMap<&Path, (&Index, Vec<&PlanEntry>)> = BTreeMap::new();
+		for entry in self.iter() {
+			let output = entry.output();
+			groups
+				.entry(output.path())
+				.or_insert_with(|| (output, Vec::new()))
+				.1
+				.push(entry);
+		}
+		groups
+	}
+}
+
+impl<'a> IntoIterator for &'a ExportPlan {
+	type Item = &'a PlanEntry;
+	type IntoIter = Iter<'a, PlanEntry>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.entries.iter()
+	}
+}
+
+impl FromIterator<PlanEntry> for ExportPlan {
+	fn from_iter<T: IntoIterator<Item = PlanEntry>>(iter: T) -> Self {
+		let entries = iter.into_iter().collect();
+		Self::new(entries)
+	}
+}
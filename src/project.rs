@@ -1,6 +1,10 @@
-use super::prelude::IndexList;
+use super::prelude::{Error, Index, IndexList};
+use super::source::{DirSource, Source};
+#[cfg(feature = "zip")]
+use super::source::ZipSource;
+use std::collections::HashSet;
 use std::fmt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// A unique Project ID that can be easily copy.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -23,14 +27,28 @@ impl fmt::Display for Pid {
 }
 
 /// Conflict handling strategy.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Strategy {
 	/// This strategy will cause [File::merge](../file/trait.File.html#method.merge) method to be call.
 	Merge,
 	/// This strategy will cause the file to be rename to some unique name and [File::modify_relation](../file/trait.File.html#method.modify_relation) method to be call on related files.
 	Rename,
+	/// Same as [Rename](#variant.Rename) except that the file from the highest priority project keep its original path.
+	///
+	/// This is useful for files that are referenced by their path from outside the workspace.
+	RenameOthers,
 	/// This strategy will cause the file to override the conflicted file entirely.
 	Replace,
+	/// Only export the file from the highest priority project, every other copy is ignored.
+	KeepFirst,
+	/// Only export the file from the lowest priority project, every other copy is ignored.
+	KeepLast,
+	/// Do not export any copy of the conflicting file.
+	Skip,
+	/// Abort the export with [Error::Conflict](../prelude/enum.Error.html#variant.Conflict).
+	///
+	/// This is useful to make sure that protected paths are never silently overridden.
+	Fail,
 }
 
 /// Project interface representing a single project directory.
@@ -41,5 +59,95 @@ pub trait Project {
 	fn pid(&self) -> Pid;
 
 	/// Return [IndexList](../index/struct.IndexList.html) of all indexes inside this project.
-	fn indexes(&self) -> IndexList;
+	fn indexes(&self) -> IndexList<'_>;
+
+	/// Return the [Source](../source/trait.Source.html) that this project's files are read from.
+	///
+	/// By default, files are read from the project's [root()](#tymethod.root) directory.
+	/// Projects that do not live in a plain directory (e.g. in memory or inside an archive) should override this method.
+	fn source(&self) -> Box<dyn Source + '_> {
+		Box::new(DirSource::new(self.root()))
+	}
+}
+
+/// Project that read every file from the given [Source](../source/trait.Source.html).
+///
+/// ```
+/// # use superfusion::project::SourceProject;
+/// # use superfusion::source::MemorySource;
+/// # use superfusion::prelude::{Pid, Project};
+/// let source = MemorySource::new().with("pack.mcmeta", "{}");
+/// let project = SourceProject::new("memory", Pid::new(0), source).unwrap();
+///
+/// assert_eq!(project.indexes().iter().count(), 1);
+/// ```
+#[derive(Debug)]
+pub struct SourceProject<S> {
+	pid: Pid,
+	root: PathBuf,
+	indexes: HashSet<Index>,
+	source: S,
+}
+
+impl<S> SourceProject<S>
+where
+	S: Source,
+{
+	/// Create a project from every file inside the given `source`.
+	///
+	/// `root` is only used to identify the project and does not need to exist.
+	pub fn new(root: impl Into<PathBuf>, pid: Pid, source: S) -> Result<Self, Error> {
+		let root = root.into();
+		let indexes = source
+			.files()?
+			.into_iter()
+			.map(|path| Index::new(pid, path))
+			.collect();
+
+		Ok(Self {
+			pid,
+			root,
+			indexes,
+			source,
+		})
+	}
+
+	pub fn inner(&self) -> &S {
+		&self.source
+	}
+}
+
+impl<S> Project for SourceProject<S>
+where
+	S: Source,
+{
+	fn root(&self) -> &Path {
+		&self.root
+	}
+
+	fn pid(&self) -> Pid {
+		self.pid
+	}
+
+	fn indexes(&self) -> IndexList<'_> {
+		self.indexes.iter().collect()
+	}
+
+	fn source(&self) -> Box<dyn Source + '_> {
+		Box::new(&self.source)
+	}
+}
+
+/// Project that read its files from a zip archive instead of a directory.
+#[cfg(feature = "zip")]
+pub type ZipProject = SourceProject<ZipSource>;
+
+#[cfg(feature = "zip")]
+impl SourceProject<ZipSource> {
+	/// Open the zip archive at the given `path` as a project
+	pub fn from_archive(path: impl Into<PathBuf>, pid: Pid) -> Result<Self, Error> {
+		let root = path.into();
+		let source = ZipSource::new(&root)?;
+		Self::new(root, pid, source)
+	}
 }
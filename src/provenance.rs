@@ -0,0 +1,181 @@
+use super::fs;
+use super::graph::DanglingRelation;
+use super::plan::PlanEntry;
+use super::prelude::{Error, Index, Strategy};
+use std::collections::btree_map::Values;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Where a single output file's content came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+	source: Index,
+	strategy: Strategy,
+	renamed: bool,
+}
+
+impl Origin {
+	/// Index of the file inside its original project
+	pub fn source(&self) -> &Index {
+		&self.source
+	}
+
+	/// Strategy that produced the output
+	pub fn strategy(&self) -> Strategy {
+		self.strategy
+	}
+
+	/// Whether the file was written to a different path than its original one
+	pub fn renamed(&self) -> bool {
+		self.renamed
+	}
+}
+
+/// Provenance of a single output file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputRecord {
+	output: Index,
+	sources: Vec<Origin>,
+	relations: Vec<Index>,
+}
+
+impl OutputRecord {
+	/// Index of the file inside the output project
+	pub fn output(&self) -> &Index {
+		&self.output
+	}
+
+	/// Every source that contributed to this output, in the order they were merged
+	pub fn sources(&self) -> &[Origin] {
+		&self.sources
+	}
+
+	/// Relations of the written file after renaming.
+	///
+	/// This is empty if the file was not written during this export (e.g. unchanged during an incremental export).
+	pub fn relations(&self) -> &[Index] {
+		&self.relations
+	}
+}
+
+/// Record of where every exported file came from.
+///
+/// This is returned by every export method of [Timeline](../timeline/struct.Timeline.html) and can optionally be saved with [save()](#method.save).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Provenance {
+	records: BTreeMap<PathBuf, OutputRecord>,
+}
+
+impl Provenance {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Record that the given `entry` was written into the output.
+	///
+	/// If `merged` is true, the entry is appended to the sources that were already written to the same output, otherwise it replace them.
+	pub(crate) fn record(&mut self, entry: &PlanEntry, merged: bool) {
+		let origin = Origin {
+			source: entry.source().clone(),
+			strategy: entry.strategy(),
+			renamed: entry.source().path() != entry.output().path(),
+		};
+
+		let path = entry.output().path().to_path_buf();
+		let record = self.records.entry(path).or_insert_with(|| OutputRecord {
+			output: entry.output().clone(),
+			sources: Vec::new(),
+			relations: Vec::new(),
+		});
+
+		if !merged {
+			record.sources.clear();
+		}
+		record.sources.push(origin);
+	}
+
+	/// Record the relations of the file that was written at the given output `path`
+	pub(crate) fn relate(&mut self, path: &Path, relations: Vec<Index>) {
+		if let Some(record) = self.records.get_mut(path) {
+			record.relations = relations;
+		}
+	}
+
+	/// Add every record of `other`, replacing the records of the same output
+	#[cfg(feature = "parallel")]
+	pub(crate) fn extend(&mut self, other: Provenance) {
+		self.records.extend(other.records);
+	}
+
+	/// Forget the output at the given `path`
+	pub(crate) fn remove(&mut self, path: &Path) {
+		self.records.remove(path);
+	}
+
+	/// Every relation in the output that point at a path that was not exported
+	pub fn dangling(&self) -> Vec<DanglingRelation> {
+		self.iter()
+			.flat_map(|record| {
+				record
+					.relations()
+					.iter()
+					.filter(|relation| !self.records.contains_key(relation.path()))
+					.map(move |relation| {
+						DanglingRelation::new(record.output().clone(), relation.clone())
+					})
+			})
+			.collect()
+	}
+
+	/// Get provenance of the output file at the given relative `path`
+	pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&OutputRecord> {
+		self.records.get(path.as_ref())
+	}
+
+	/// Create iterator over every output record, sorted by output path
+	pub fn iter(&self) -> Values<'_, PathBuf, OutputRecord> {
+		self.records.values()
+	}
+
+	pub fn len(&self) -> usize {
+		self.records.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.records.is_empty()
+	}
+
+	/// Save this provenance as a tab-separated file at the given `path`.
+	///
+	/// Each line describe one source of an output file: `output, pid, source, strategy, renamed`.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		let mut content = String::from("output\tpid\tsource\tstrategy\trenamed\n");
+
+		for record in self.iter() {
+			for origin in record.sources() {
+				let line = format!(
+					"{}\t{}\t{}\t{:?}\t{}\n",
+					record.output().path().display(),
+					origin.source().pid().value(),
+					origin.source().path().display(),
+					origin.strategy(),
+					origin.renamed()
+				);
+				content.push_str(&line);
+			}
+		}
+
+		let path = path.as_ref();
+		fs::prepare_parent(path)?;
+		fs::write(path, content)
+	}
+}
+
+impl<'a> IntoIterator for &'a Provenance {
+	type Item = &'a OutputRecord;
+	type IntoIter = Values<'a, PathBuf, OutputRecord>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.records.values()
+	}
+}
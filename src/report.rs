@@ -0,0 +1,111 @@
+use super::prelude::{Error, Index, Pid, Provenance};
+use std::fmt;
+
+/// Step of the export that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+	/// Reading the file from its project
+	Load,
+	/// Merging the file with the one already in the output
+	Merge,
+	/// Writing the file into the output
+	Write,
+}
+
+impl fmt::Display for Operation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let operation = match self {
+			Operation::Load => "load",
+			Operation::Merge => "merge",
+			Operation::Write => "write",
+		};
+		f.write_str(operation)
+	}
+}
+
+/// A single index that could not be exported.
+#[derive(Debug)]
+pub struct ExportFailure {
+	index: Index,
+	operation: Operation,
+	error: Error,
+}
+
+impl ExportFailure {
+	pub fn new(index: Index, operation: Operation, error: Error) -> Self {
+		Self {
+			index,
+			operation,
+			error,
+		}
+	}
+
+	/// Index of the file inside its original project
+	pub fn index(&self) -> &Index {
+		&self.index
+	}
+
+	/// Pid of the project that the file came from
+	pub fn pid(&self) -> &Pid {
+		self.index.pid()
+	}
+
+	pub fn operation(&self) -> Operation {
+		self.operation
+	}
+
+	pub fn error(&self) -> &Error {
+		&self.error
+	}
+
+	pub fn into_error(self) -> Error {
+		self.error
+	}
+}
+
+impl fmt::Display for ExportFailure {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"Unable to {} {}: {}",
+			self.operation, self.index, self.error
+		)
+	}
+}
+
+/// Result of an export that keep going after a file failed to export.
+///
+/// This is returned by [Timeline::export_to_lenient()](../timeline/struct.Timeline.html#method.export_to_lenient).
+#[derive(Debug, Default)]
+pub struct ExportReport {
+	provenance: Provenance,
+	failures: Vec<ExportFailure>,
+}
+
+impl ExportReport {
+	pub fn new(provenance: Provenance, failures: Vec<ExportFailure>) -> Self {
+		Self {
+			provenance,
+			failures,
+		}
+	}
+
+	/// Provenance of every file that was successfully exported
+	pub fn provenance(&self) -> &Provenance {
+		&self.provenance
+	}
+
+	/// Every index that failed to export, in export order
+	pub fn failures(&self) -> &[ExportFailure] {
+		&self.failures
+	}
+
+	/// Check if every index was exported
+	pub fn is_success(&self) -> bool {
+		self.failures.is_empty()
+	}
+
+	pub fn into_parts(self) -> (Provenance, Vec<ExportFailure>) {
+		(self.provenance, self.failures)
+	}
+}
@@ -0,0 +1,178 @@
+use super::fs;
+use super::prelude::{Conflict, Error, Project, Strategy};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+const HEADER: &str = "path\tpids\tstrategy";
+
+/// Interface for choosing a strategy for every conflict, e.g. by prompting a human.
+///
+/// This is consulted by [Workspace::resolve_with()](../workspace/trait.Workspace.html#method.resolve_with) once for every conflicting path.
+pub trait ConflictResolver<P: Project> {
+	/// Choose a strategy for the given `conflict`.
+	///
+	/// `projects` contain the project of every conflicting index, in the same order as [Conflict::indexes()](../conflict/struct.Conflict.html#method.indexes).
+	/// The conflict's [strategy()](../conflict/struct.Conflict.html#method.strategy) is the one chosen by the workspace and is kept when this return `None`.
+	fn choose(&mut self, conflict: &Conflict, projects: &[&P]) -> Option<Strategy>;
+}
+
+/// Strategies that were chosen for previous conflicts.
+///
+/// A decision is identified by the conflicting path and the pid of every project involved,
+/// so it is only replayed when the exact same projects conflict on the same path again.
+///
+/// ```
+/// # use superfusion::conflict::Conflict;
+/// # use superfusion::resolver::Decisions;
+/// # use superfusion::prelude::{Index, Pid, Strategy};
+/// let indexes = vec![Index::new(Pid::new(0), "foo.json"), Index::new(Pid::new(1), "foo.json")];
+/// let conflict = Conflict::new("foo.json", indexes, Strategy::Merge);
+///
+/// let mut decisions = Decisions::new();
+/// decisions.insert(&conflict, Strategy::Rename);
+/// assert_eq!(decisions.get(&conflict), Some(Strategy::Rename));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Decisions {
+	decisions: BTreeMap<(PathBuf, Vec<usize>), Strategy>,
+}
+
+impl Decisions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	fn key(conflict: &Conflict) -> (PathBuf, Vec<usize>) {
+		let mut pids: Vec<_> = conflict.pids().map(|pid| pid.value()).collect();
+		pids.sort_unstable();
+		(conflict.path().to_path_buf(), pids)
+	}
+
+	/// Get the strategy that was chosen for the given `conflict`
+	pub fn get(&self, conflict: &Conflict) -> Option<Strategy> {
+		self.decisions.get(&Self::key(conflict)).copied()
+	}
+
+	/// Record the strategy chosen for the given `conflict`
+	pub fn insert(&mut self, conflict: &Conflict, strategy: Strategy) {
+		self.decisions.insert(Self::key(conflict), strategy);
+	}
+
+	pub fn len(&self) -> usize {
+		self.decisions.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.decisions.is_empty()
+	}
+
+	/// Create a resolver that replay these decisions and record every new decision made by `resolver`
+	pub fn record<R>(&mut self, resolver: R) -> Recorder<'_, R> {
+		Recorder {
+			decisions: self,
+			resolver,
+		}
+	}
+
+	/// Load decisions saved by [save()](#method.save).
+	///
+	/// A missing file is treated as no decision.
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		let path = path.as_ref();
+		if !path.exists() {
+			return Ok(Self::new());
+		}
+
+		let content = std::fs::read_to_string(path).map_err(|io| Error::read(path, io))?;
+		let mut decisions = Self::new();
+
+		for line in content.lines().filter(|&line| line != HEADER) {
+			let decision = parse_line(line).ok_or_else(|| Error::invalid_decision(path, line))?;
+			decisions.decisions.insert(decision.0, decision.1);
+		}
+
+		Ok(decisions)
+	}
+
+	/// Save these decisions as a tab-separated file at the given `path`.
+	///
+	/// Each line describe one decision: `path, pids, strategy` where `pids` is a comma-separated list.
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+		let mut content = format!("{}\n", HEADER);
+
+		for ((file, pids), strategy) in &self.decisions {
+			let pids: Vec<_> = pids.iter().map(usize::to_string).collect();
+			let line = format!("{}\t{}\t{:?}\n", file.display(), pids.join(","), strategy);
+			content.push_str(&line);
+		}
+
+		let path = path.as_ref();
+		fs::prepare_parent(path)?;
+		fs::write(path, content)
+	}
+}
+
+/// Replay the recorded decisions without consulting anyone
+impl<P: Project> ConflictResolver<P> for Decisions {
+	fn choose(&mut self, conflict: &Conflict, _projects: &[&P]) -> Option<Strategy> {
+		self.get(conflict)
+	}
+}
+
+/// Resolver that replay previous [Decisions](struct.Decisions.html) and only consult the inner resolver for new conflicts.
+///
+/// Every strategy chosen by the inner resolver is recorded into the decisions.
+#[derive(Debug)]
+pub struct Recorder<'d, R> {
+	decisions: &'d mut Decisions,
+	resolver: R,
+}
+
+impl<'d, R> Recorder<'d, R> {
+	pub fn into_inner(self) -> R {
+		self.resolver
+	}
+}
+
+impl<'d, P, R> ConflictResolver<P> for Recorder<'d, R>
+where
+	P: Project,
+	R: ConflictResolver<P>,
+{
+	fn choose(&mut self, conflict: &Conflict, projects: &[&P]) -> Option<Strategy> {
+		if let Some(strategy) = self.decisions.get(conflict) {
+			return Some(strategy);
+		}
+
+		let strategy = self.resolver.choose(conflict, projects)?;
+		self.decisions.insert(conflict, strategy);
+		Some(strategy)
+	}
+}
+
+fn parse_line(line: &str) -> Option<((PathBuf, Vec<usize>), Strategy)> {
+	let mut parts = line.split('\t');
+	let path = PathBuf::from(parts.next()?);
+	let pids = parts
+		.next()?
+		.split(',')
+		.map(|pid| pid.parse().ok())
+		.collect::<Option<Vec<_>>>()?;
+	let strategy = parse_strategy(parts.next()?)?;
+	Some(((path, pids), strategy))
+}
+
+fn parse_strategy(value: &str) -> Option<Strategy> {
+	let strategy = match value {
+		"Merge" => Strategy::Merge,
+		"Rename" => Strategy::Rename,
+		"RenameOthers" => Strategy::RenameOthers,
+		"Replace" => Strategy::Replace,
+		"KeepFirst" => Strategy::KeepFirst,
+		"KeepLast" => Strategy::KeepLast,
+		"Skip" => Strategy::Skip,
+		"Fail" => Strategy::Fail,
+		_ => return None,
+	};
+	Some(strategy)
+}
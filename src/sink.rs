@@ -0,0 +1,142 @@
+#[cfg(feature = "zip")]
+mod archive;
+#[cfg(feature = "zip")]
+pub use archive::ZipSink;
+
+use super::fs;
+use super::prelude::Error;
+use std::collections::btree_map::Iter;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Destination of an export.
+///
+/// Every path given to the sink is relative to the root of the output project, the same as [Index::path()](../index/struct.Index.html#method.path).
+/// Export only ever write into the sink, [read()](#tymethod.read) is used by incremental export to compare the previous output.
+pub trait OutputSink {
+	/// Check if the file at the given `path` was already written
+	fn exists(&self, path: &Path) -> bool;
+
+	/// Write `data` into the file at the given `path`, replacing it if it already exists
+	fn write(&mut self, path: &Path, data: &[u8]) -> Result<(), Error>;
+
+	/// Open the file at the given `path` that was previously written into this sink
+	fn read(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error>;
+
+	/// Write everything from `reader` into the file at the given `path`, replacing it if it already exists.
+	///
+	/// This is used to copy files that do not need to be loaded, sinks that can stream the content should override this method.
+	/// By default, the whole content is read into memory and passed to [write()](#tymethod.write).
+	fn write_from(&mut self, path: &Path, reader: &mut dyn Read) -> Result<(), Error> {
+		let mut data = Vec::new();
+		reader
+			.read_to_end(&mut data)
+			.map_err(|io| Error::read(path, io))?;
+		self.write(path, &data)
+	}
+}
+
+/// Sink that write every file into a directory on the filesystem.
+#[derive(Debug, Clone)]
+pub struct DirSink {
+	root: PathBuf,
+}
+
+impl DirSink {
+	pub fn new(root: impl Into<PathBuf>) -> Self {
+		let root = root.into();
+		Self { root }
+	}
+
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+
+	/// Join the given `path` to the root, refusing any path that would lead outside of it
+	fn join(&self, path: &Path) -> Result<PathBuf, Error> {
+		fs::relative(path).map(|path| self.root.join(path))
+	}
+}
+
+impl OutputSink for DirSink {
+	fn exists(&self, path: &Path) -> bool {
+		matches!(self.join(path), Ok(path) if path.exists())
+	}
+
+	fn write(&mut self, path: &Path, data: &[u8]) -> Result<(), Error> {
+		let path = self.join(path)?;
+		fs::prepare_parent(&path)?;
+		fs::write(path, data)
+	}
+
+	fn read(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error> {
+		let file = fs::open(self.join(path)?)?;
+		Ok(Box::new(file))
+	}
+
+	fn write_from(&mut self, path: &Path, reader: &mut dyn Read) -> Result<(), Error> {
+		fs::copy_from(self.join(path)?, reader)
+	}
+}
+
+/// Sink that keep every file in memory.
+///
+/// ```
+/// # use superfusion::sink::{MemorySink, OutputSink};
+/// # use std::path::Path;
+/// let mut sink = MemorySink::new();
+/// sink.write(Path::new("data/foo.json"), b"{}").unwrap();
+///
+/// assert!(sink.exists(Path::new("data/foo.json")));
+/// assert_eq!(sink.get("data/foo.json"), Some(&b"{}"[..]));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MemorySink {
+	files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl MemorySink {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Get content of the file at the given `path`
+	pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&[u8]> {
+		self.files.get(path.as_ref()).map(Vec::as_slice)
+	}
+
+	/// Create iterator over every file in this sink, sorted by path
+	pub fn iter(&self) -> Iter<'_, PathBuf, Vec<u8>> {
+		self.files.iter()
+	}
+
+	pub fn len(&self) -> usize {
+		self.files.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.files.is_empty()
+	}
+
+	/// Take the internal's `BTreeMap` of 'path → content'
+	pub fn into_inner(self) -> BTreeMap<PathBuf, Vec<u8>> {
+		self.files
+	}
+}
+
+impl OutputSink for MemorySink {
+	fn exists(&self, path: &Path) -> bool {
+		self.files.contains_key(path)
+	}
+
+	fn write(&mut self, path: &Path, data: &[u8]) -> Result<(), Error> {
+		self.files.insert(path.to_path_buf(), data.to_vec());
+		Ok(())
+	}
+
+	fn read(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error> {
+		let data = self.get(path).ok_or_else(|| Error::not_found(path))?;
+		Ok(Box::new(data))
+	}
+}
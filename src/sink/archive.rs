@@ -0,0 +1,76 @@
+use super::{MemorySink, OutputSink};
+use crate::fs;
+use crate::prelude::Error;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+/// Sink that pack every file into a zip archive.
+///
+/// Files are kept in memory until [finish()](#method.finish) is called so that they can still be merged.
+/// The archive is reproducible: entries are sorted by path and every entry share the same timestamp and permission.
+///
+/// ```
+/// # use superfusion::sink::{OutputSink, ZipSink};
+/// # use std::io::Cursor;
+/// # use std::path::Path;
+/// let mut sink = ZipSink::new(Cursor::new(Vec::new()));
+/// sink.write(Path::new("pack.mcmeta"), b"{}").unwrap();
+///
+/// let archive = sink.finish().unwrap().into_inner();
+/// assert!(!archive.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct ZipSink<W> {
+	writer: W,
+	files: MemorySink,
+}
+
+impl<W> ZipSink<W>
+where
+	W: Write + Seek,
+{
+	pub fn new(writer: W) -> Self {
+		Self {
+			writer,
+			files: MemorySink::new(),
+		}
+	}
+
+	/// Write every file into the archive and return the underlying writer
+	pub fn finish(self) -> Result<W, Error> {
+		let mut archive = ZipWriter::new(self.writer);
+		let options = FileOptions::default()
+			.compression_method(CompressionMethod::Deflated)
+			.last_modified_time(DateTime::default())
+			.unix_permissions(0o644);
+
+		for (path, data) in self.files.iter() {
+			let name = fs::entry_name(path)?;
+			archive.start_file(name, options).map_err(Error::archive)?;
+			archive
+				.write_all(data)
+				.map_err(|io| Error::archive(io.into()))?;
+		}
+
+		archive.finish().map_err(Error::archive)
+	}
+}
+
+impl<W> OutputSink for ZipSink<W>
+where
+	W: Write + Seek,
+{
+	fn exists(&self, path: &Path) -> bool {
+		self.files.exists(path)
+	}
+
+	fn write(&mut self, path: &Path, data: &[u8]) -> Result<(), Error> {
+		self.files.write(path, data)
+	}
+
+	fn read(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error> {
+		self.files.read(path)
+	}
+}
@@ -0,0 +1,111 @@
+#[cfg(feature = "zip")]
+mod archive;
+#[cfg(feature = "zip")]
+pub use archive::ZipSource;
+
+use super::fs;
+use super::prelude::Error;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A virtual filesystem that project's files are read from.
+///
+/// Every path given to the source is relative to the root of the project, the same as [Index::path()](../index/struct.Index.html#method.path).
+/// This allow projects to live in a directory, in memory, inside an archive or anywhere else.
+pub trait Source {
+	/// Return the relative path of every file inside this source
+	fn files(&self) -> Result<Vec<PathBuf>, Error>;
+
+	/// Open the file at the given `path` for reading
+	fn open(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error>;
+}
+
+impl<S: Source + ?Sized> Source for &S {
+	fn files(&self) -> Result<Vec<PathBuf>, Error> {
+		(**self).files()
+	}
+
+	fn open(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error> {
+		(**self).open(path)
+	}
+}
+
+/// Source that read files from a directory on the filesystem.
+#[derive(Debug, Clone)]
+pub struct DirSource {
+	root: PathBuf,
+}
+
+impl DirSource {
+	pub fn new(root: impl Into<PathBuf>) -> Self {
+		let root = root.into();
+		Self { root }
+	}
+
+	pub fn root(&self) -> &Path {
+		&self.root
+	}
+}
+
+impl Source for DirSource {
+	fn files(&self) -> Result<Vec<PathBuf>, Error> {
+		let mut files = fs::walk(&self.root)?;
+		files.sort();
+		Ok(files)
+	}
+
+	fn open(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error> {
+		let file = fs::open(self.root.join(path))?;
+		Ok(Box::new(file))
+	}
+}
+
+/// Source that keep every file in memory.
+///
+/// ```
+/// # use superfusion::source::{MemorySource, Source};
+/// # use std::io::Read;
+/// # use std::path::Path;
+/// let source = MemorySource::new()
+///     .with("pack.mcmeta", "{}")
+///     .with("data/foo.json", "[]");
+///
+/// let mut content = String::new();
+/// source.open(Path::new("data/foo.json")).unwrap().read_to_string(&mut content).unwrap();
+///
+/// assert_eq!(source.files().unwrap().len(), 2);
+/// assert_eq!(content, "[]");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MemorySource {
+	files: BTreeMap<PathBuf, Vec<u8>>,
+}
+
+impl MemorySource {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Insert a file into this source
+	pub fn with(mut self, path: impl Into<PathBuf>, data: impl Into<Vec<u8>>) -> Self {
+		self.insert(path, data);
+		self
+	}
+
+	/// Insert a file into this source, replacing the previous content
+	pub fn insert(&mut self, path: impl Into<PathBuf>, data: impl Into<Vec<u8>>) {
+		self.files.insert(path.into(), data.into());
+	}
+}
+
+impl Source for MemorySource {
+	fn files(&self) -> Result<Vec<PathBuf>, Error> {
+		Ok(self.files.keys().cloned().collect())
+	}
+
+	fn open(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error> {
+		let data = self.files.get(path).ok_or_else(|| Error::not_found(path))?;
+		Ok(Box::new(data.as_slice()))
+	}
+}
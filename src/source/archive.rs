@@ -0,0 +1,59 @@
+use super::Source;
+use crate::fs;
+use crate::prelude::Error;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use zip::ZipArchive;
+
+/// Source that read files from a zip archive.
+///
+/// The root of the source is the root of the archive.
+#[derive(Debug)]
+pub struct ZipSource {
+	archive: Mutex<ZipArchive<std::fs::File>>,
+}
+
+impl ZipSource {
+	/// Open the zip archive at the given `path`
+	pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+		let file = fs::open(path)?;
+		let archive = ZipArchive::new(file).map_err(Error::archive)?;
+		let archive = Mutex::new(archive);
+		Ok(Self { archive })
+	}
+}
+
+impl Source for ZipSource {
+	/// List every file of the archive, entries that would lead outside of the archive root are refused
+	fn files(&self) -> Result<Vec<PathBuf>, Error> {
+		let mut archive = self.archive.lock().unwrap_or_else(|e| e.into_inner());
+		let mut files = Vec::with_capacity(archive.len());
+		for i in 0..archive.len() {
+			let entry = archive.by_index_raw(i).map_err(Error::archive)?;
+			if entry.is_dir() {
+				continue;
+			}
+
+			let path = fs::relative(Path::new(entry.name()))?;
+			files.push(path.to_path_buf());
+		}
+		files.sort();
+		Ok(files)
+	}
+
+	/// Decompress the whole entry into memory since the archive is locked while an entry is read.
+	///
+	/// Files from a zip source are never streamed, even when they are [passed through](../workspace/trait.Workspace.html#method.pass_through).
+	fn open(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error> {
+		let name = fs::entry_name(path)?;
+		let mut archive = self.archive.lock().unwrap_or_else(|e| e.into_inner());
+		let mut entry = archive.by_name(&name).map_err(Error::archive)?;
+
+		let mut data = Vec::new();
+		entry
+			.read_to_end(&mut data)
+			.map_err(|io| Error::read(path, io))?;
+		Ok(Box::new(Cursor::new(data)))
+	}
+}
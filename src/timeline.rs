@@ -1,15 +1,34 @@
+use super::conflict::ConflictReport;
 use super::fs;
-use super::prelude::{Error, File, Index, IndexMapping, Pid, Strategy, Workspace};
+use super::graph::{DanglingRelation, RelationGraph};
+use super::plan::{ExportPlan, PlanEntry};
+use super::provenance::Provenance;
+use super::report::{ExportFailure, ExportReport, Operation};
+use super::prelude::{
+	Error, File, Index, IndexMapping, OutputSink, Pid, Project, Relation, Strategy, Workspace,
+};
+use super::sink::DirSink;
+#[cfg(feature = "zip")]
+use super::sink::ZipSink;
+use super::manifest::{self, Manifest, Record};
 use log::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use std::collections::hash_map::Iter;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::slice;
 
 /// A handle containing information describing how to merge the projects together.
-pub struct Timeline<'a, W> {
-	strategy: HashMap<&'a Index, Strategy>,
-	projects: HashMap<Pid, &'a Path>,
-	_workspace: std::marker::PhantomData<W>,
+pub struct Timeline<'a, W>
+where
+	W: Workspace,
+{
+	strategy: Vec<(&'a Index, Strategy)>,
+	projects: HashMap<Pid, &'a W::Project>,
+	priority: Vec<Pid>,
+	conflicts: ConflictReport,
 }
 
 impl<'a, W> Timeline<'a, W>
@@ -17,8 +36,10 @@ where
 	W: Workspace,
 {
 	pub(crate) fn new(
-		strategy: HashMap<&'a Index, Strategy>,
-		projects: HashMap<Pid, &'a Path>,
+		strategy: Vec<(&'a Index, Strategy)>,
+		projects: HashMap<Pid, &'a W::Project>,
+		priority: Vec<Pid>,
+		conflicts: ConflictReport,
 	) -> Self {
 		debug!(
 			"Create new timeline with {} projects and {} strategies",
@@ -28,7 +49,8 @@ where
 		Self {
 			strategy,
 			projects,
-			_workspace: std::marker::PhantomData,
+			priority,
+			conflicts,
 		}
 	}
 
@@ -37,60 +59,435 @@ where
 		Pid::new(self.projects.len())
 	}
 
-	pub fn strategy(&self) -> Iter<&Index, Strategy> {
+	/// Every index with its strategy, in the order they will be exported
+	pub fn strategy(&self) -> slice::Iter<'_, (&Index, Strategy)> {
 		self.strategy.iter()
 	}
 
-	pub fn projects(&self) -> Iter<Pid, &Path> {
+	pub fn projects(&self) -> Iter<'_, Pid, &W::Project> {
 		self.projects.iter()
 	}
 
-	fn mapping(&self) -> Result<IndexMapping, Error> {
-		let mut map = HashMap::new();
+	/// Project's priority, from the highest to the lowest
+	pub fn priority(&self) -> &[Pid] {
+		&self.priority
+	}
+
+	/// Every conflicting path that was found while resolving the workspace
+	pub fn conflicts(&self) -> &ConflictReport {
+		&self.conflicts
+	}
+
+	/// Describe what [export_to()](#method.export_to) is going to do without touching the filesystem.
+	///
+	/// The returned [ExportPlan](../plan/struct.ExportPlan.html) list every source index, its strategy and the output index it will be written to.
+	/// Indexes that are left out by [Strategy::KeepFirst](../project/enum.Strategy.html#variant.KeepFirst), [KeepLast](../project/enum.Strategy.html#variant.KeepLast)
+	/// or [Skip](../project/enum.Strategy.html#variant.Skip) are listed in [ExportPlan::dropped()](../plan/struct.ExportPlan.html#method.dropped)
+	/// and any conflict under [Fail](../project/enum.Strategy.html#variant.Fail) is returned as an error.
+	pub fn plan(&self) -> Result<ExportPlan, Error> {
 		let oid = self.output_id();
+		let mut taken = self
+			.indexes()
+			.map(|(index, _)| index.path().to_path_buf())
+			.collect();
 
+		let mut entries = Vec::new();
+		let mut dropped = Vec::new();
 		for (index, strategy) in self.indexes() {
-			match strategy {
-				Strategy::Replace | Strategy::Merge => map.insert(index, index.with_pid(oid)),
-				Strategy::Rename => {
-					let renamed = index.rename(W::formatter)?.with_pid(oid);
-					map.insert(index, renamed)
+			let output = match strategy {
+				Strategy::Replace | Strategy::Merge => index.with_pid(oid),
+				Strategy::RenameOthers if self.is_highest_priority(index) => index.with_pid(oid),
+				Strategy::Rename | Strategy::RenameOthers => {
+					unique_rename::<W>(index, &mut taken)?.with_pid(oid)
+				}
+				Strategy::KeepFirst if self.is_highest_priority(index) => index.with_pid(oid),
+				Strategy::KeepLast if self.is_lowest_priority(index) => index.with_pid(oid),
+				Strategy::KeepFirst | Strategy::KeepLast | Strategy::Skip => {
+					debug!("Leave {} out of the export with {:?}", index, strategy);
+					dropped.push((index.clone(), strategy));
+					continue;
 				}
+				Strategy::Fail => return Err(self.forbidden_conflict(index)),
 			};
+
+			entries.push(PlanEntry::new(index.clone(), strategy, output));
 		}
 
-		Ok(IndexMapping::new(map))
+		Ok(ExportPlan::with_dropped(entries, dropped))
 	}
 
-	fn exporter<P: Into<PathBuf>>(&self, root: P, mapping: IndexMapping<'a>) -> Exporter<'a, W> {
-		let oid = self.output_id();
-		let root = root.into();
-		let output_project = std::iter::once((oid, root.clone()));
+	/// Build a [RelationGraph](../graph/struct.RelationGraph.html) from the relations of every file in the workspace.
+	///
+	/// Every file is loaded to read its [relation()](../file/trait.File.html#tymethod.relation), the same as an export does.
+	/// Files that are [passed through](../workspace/trait.Workspace.html#method.pass_through) are added without any relation.
+	pub fn relations(&self) -> Result<RelationGraph, Error> {
+		let mut graph = RelationGraph::new();
+
+		for (index, _) in self.indexes() {
+			graph.add(index.clone());
+			if W::pass_through(index) {
+				continue;
+			}
+
+			let file = load_file::<W>(&self.projects, index)?;
+			for relation in file.relation() {
+				graph.insert(index.clone(), relation.index());
+			}
+		}
+
+		Ok(graph)
+	}
+
+	/// Find every relation that point at a path that no project contain.
+	///
+	/// This check the workspace before export, use [Provenance::dangling()](../provenance/struct.Provenance.html#method.dangling) to check the output afterward.
+	pub fn dangling(&self) -> Result<Vec<DanglingRelation>, Error> {
+		let paths: HashSet<_> = self.indexes().map(|(index, _)| index.path()).collect();
+		let graph = self.relations()?;
+		Ok(graph.dangling(|index| paths.contains(index.path())))
+	}
+
+	/// Drop every file that can not be reached from the roots by following their relations.
+	///
+	/// Roots are the source indexes that satisfy `is_root`, every output they are written to is kept
+	/// along with every output that any of its sources reference, directly or not.
+	/// Since every source of a kept output is loaded, a merged file keep the relations of every project that contributed to it.
+	///
+	/// The returned timeline only contain the kept indexes and can be exported like any other timeline.
+	pub fn tree_shake<F>(self, is_root: F) -> Result<Self, Error>
+	where
+		F: Fn(&Index) -> bool,
+	{
+		let plan = self.plan()?;
+		let mapping = plan.mapping();
 
-		let projects = self
-			.projects()
-			.map(|(&pid, path)| (pid, path.to_path_buf()))
-			.chain(output_project)
+		let mut outputs: HashMap<&Path, Vec<&Index>> = HashMap::new();
+		for entry in &plan {
+			outputs
+				.entry(entry.output().path())
+				.or_default()
+				.push(entry.source());
+		}
+
+		let mut queue: VecDeque<PathBuf> = plan
+			.iter()
+			.filter(|entry| is_root(entry.source()))
+			.map(|entry| entry.output().path().to_path_buf())
+			.collect();
+		let mut reached = HashSet::new();
+
+		while let Some(path) = queue.pop_front() {
+			let sources = match outputs.get(path.as_path()) {
+				Some(sources) if !reached.contains(&path) => sources,
+				_ => continue,
+			};
+
+			for &index in sources.iter().filter(|&&index| !W::pass_through(index)) {
+				let file = load_file::<W>(&self.projects, index)?;
+				for relation in file.relation() {
+					let to = relation.index();
+					let output = mapping.get(&to).map_or(to.path(), Index::path);
+					queue.push_back(output.to_path_buf());
+				}
+			}
+
+			reached.insert(path);
+		}
+
+		let kept: HashSet<_> = plan
+			.iter()
+			.filter(|entry| reached.contains(entry.output().path()))
+			.map(PlanEntry::source)
 			.collect();
-		Exporter::new(root, oid, projects, mapping)
+		debug!("Tree shaking keep {} of {} indexes", kept.len(), plan.len());
+
+		let Self {
+			strategy,
+			projects,
+			priority,
+			conflicts,
+		} = self;
+		let strategy = strategy
+			.into_iter()
+			.filter(|(index, _)| kept.contains(index))
+			.collect();
+		Ok(Self::new(strategy, projects, priority, conflicts))
+	}
+
+	/// Check if the given index is the highest priority copy of its path
+	fn is_highest_priority(&self, index: &Index) -> bool {
+		self.conflicts
+			.get(index.path())
+			.and_then(|conflict| conflict.indexes().last())
+			.filter(|&highest| highest != index)
+			.is_none()
+	}
+
+	/// Check if the given entry is copied without loading and then replaced by a higher priority copy
+	fn is_overwritten(&self, entry: &PlanEntry) -> bool {
+		let index = entry.source();
+		entry.strategy() == Strategy::Replace && W::pass_through(index) && !self.is_highest_priority(index)
+	}
+
+	/// Check if the given index is the lowest priority copy of its path
+	fn is_lowest_priority(&self, index: &Index) -> bool {
+		self.conflicts
+			.get(index.path())
+			.and_then(|conflict| conflict.indexes().first())
+			.filter(|&lowest| lowest != index)
+			.is_none()
+	}
+
+	fn forbidden_conflict(&self, index: &Index) -> Error {
+		let indexes = self
+			.conflicts
+			.get(index.path())
+			.map(|conflict| conflict.indexes().to_vec())
+			.unwrap_or_else(|| vec![index.clone()]);
+		Error::conflict(index.path(), indexes)
+	}
+
+	fn exporter<'p, 's, S: OutputSink>(
+		&'p self,
+		sink: &'s mut S,
+		mapping: &'p IndexMapping<'p>,
+	) -> Exporter<'p, 's, W, S> {
+		Exporter::new(sink, &self.projects, mapping)
 	}
 
 	/// Save the merged project into the given `path`
-	pub fn export_to<P>(self, path: P) -> Result<(), Error>
+	///
+	/// The export is atomic: every file is written into a staging directory next to `path` first
+	/// and only moved into place once the whole export succeeded.
+	/// On error, the staging directory is removed and the previous content of `path` is left untouched.
+	/// A `path` ending with `.` or `..` is resolved to the existing directory it point at.
+	pub fn export_to<P>(self, path: P) -> Result<Provenance, Error>
 	where
 		P: Into<PathBuf>,
 	{
-		let mapping = self.mapping()?;
-		let exporter = self.exporter(path, mapping);
+		stage(path.into(), |sink| self.export_into(sink))
+	}
 
-		for (index, strategy) in self.indexes() {
-			debug!("Export {} with {:?}", index, strategy);
-			let already_exists = exporter.exist_in_output(index);
+	/// Save the merged project into the given `path`, skipping the files that fail to export.
+	///
+	/// Every failure is collected into the returned [ExportReport](../report/struct.ExportReport.html) along with the index and the operation that failed.
+	/// Like [export_to()](#method.export_to), the output is staged first and the export is still aborted by an error that is not caused by a single file.
+	/// When merging a file fail, the merged output is left out entirely.
+	pub fn export_to_lenient<P>(self, path: P) -> Result<ExportReport, Error>
+	where
+		P: Into<PathBuf>,
+	{
+		stage(path.into(), |sink| self.export_lenient(sink))
+	}
+
+	/// Save the merged project as a zip archive at the given `path`
+	///
+	/// Like [export_to()](#method.export_to), the archive is written next to `path` first and only moved into place once it is complete.
+	#[cfg(feature = "zip")]
+	pub fn export_to_zip<P>(self, path: P) -> Result<Provenance, Error>
+	where
+		P: Into<PathBuf>,
+	{
+		let target = fs::resolve(&path.into())?;
+		let staging = fs::sibling(&target, "staging")?;
+		debug!("Stage zip export of {} in {}", target.display(), staging.display());
+
+		let result = fs::create(&staging).and_then(|file| {
+			let mut sink = ZipSink::new(file);
+			let provenance = self.export_into(&mut sink)?;
+			sink.finish()?;
+			Ok(provenance)
+		});
+
+		match result {
+			Ok(provenance) => {
+				fs::rename(&staging, &target)?;
+				Ok(provenance)
+			}
+			Err(error) => {
+				debug!("Export failed, roll back {}", staging.display());
+				if let Err(cleanup) = fs::remove_file(&staging) {
+					warn!("Unable to remove staging archive {}: {}", staging.display(), cleanup);
+				}
+				Err(error)
+			}
+		}
+	}
+
+	/// Save the merged project into the given [OutputSink](../sink/trait.OutputSink.html)
+	pub fn export_with<S>(self, sink: &mut S) -> Result<Provenance, Error>
+	where
+		S: OutputSink,
+	{
+		self.export_into(sink)
+	}
 
-			if let Some(file) = exporter.file(&index) {
-				match strategy {
-					Strategy::Merge if already_exists => exporter.merge(file, index)?,
-					_ => exporter.write(file, index)?,
+	/// Save the merged project into the given `path`, only exporting the outputs that changed since the last run.
+	///
+	/// The hashes of every source and output are recorded in the `manifest` file.
+	/// On the next run, an output is only loaded and written again when one of its sources changed, its strategy changed,
+	/// the file inside `path` was modified or any index was renamed differently.
+	/// Outputs that no longer exist in the workspace are removed from `path`.
+	///
+	/// Unlike [export_to()](#method.export_to), this write into `path` directly.
+	/// If the export fail, the manifest is removed so that the next run export everything again.
+	pub fn export_incremental<P, M>(self, path: P, manifest: M) -> Result<Provenance, Error>
+	where
+		P: Into<PathBuf>,
+		M: AsRef<Path>,
+	{
+		let manifest = manifest.as_ref();
+		let previous = Manifest::load(manifest);
+		fs::remove_file(manifest)?;
+
+		let mut sink = DirSink::new(path);
+		let (current, provenance) = self.export_changes(&previous, &mut sink)?;
+		current.save(manifest)?;
+		Ok(provenance)
+	}
+
+	fn export_changes(
+		&self,
+		previous: &Manifest,
+		sink: &mut DirSink,
+	) -> Result<(Manifest, Provenance), Error> {
+		let plan = self.plan()?;
+
+		let renamed: Vec<_> = plan
+			.iter()
+			.filter(|entry| entry.source().path() != entry.output().path())
+			.map(|entry| (entry.source(), entry.output()))
+			.collect();
+		let mut current = Manifest::new(manifest::hash(&renamed));
+		let rebuild = current.mapping() != previous.mapping();
+		debug!("Incremental export, full rebuild: {}", rebuild);
+
+		let mut inputs: BTreeMap<&Path, Vec<_>> = BTreeMap::new();
+		for entry in &plan {
+			let content = self.content_hash(entry.source())?;
+			inputs
+				.entry(entry.output().path())
+				.or_default()
+				.push((entry.strategy(), entry.source(), content));
+		}
+
+		for path in previous.paths().filter(|path| !inputs.contains_key(path)) {
+			debug!("Remove stale output {}", path.display());
+			fs::remove_file(&sink.root().join(path))?;
+		}
+
+		let mut changed = HashSet::new();
+		for (&path, input) in &inputs {
+			let input = manifest::hash(input);
+			let record = previous
+				.get(path)
+				.copied()
+				.filter(|record| !rebuild && record.input == input && sink.exists(path));
+
+			let record = match record {
+				Some(record) if output_hash(sink, path)? == record.output => record,
+				_ => {
+					debug!("Output {} changed", path.display());
+					fs::remove_file(&sink.root().join(path))?;
+					changed.insert(path);
+					Record { input, output: 0 }
+				}
+			};
+			current.insert(path.to_path_buf(), record);
+		}
+
+		let entries = plan
+			.iter()
+			.filter(|entry| changed.contains(entry.output().path()));
+		let mapping = plan.mapping();
+		let mut provenance = Provenance::new();
+		self.export_entries(&mapping, entries, sink, &mut provenance, abort)?;
+
+		let unchanged = plan
+			.iter()
+			.filter(|entry| !changed.contains(entry.output().path()));
+		for entry in unchanged {
+			let merged = provenance.get(entry.output().path()).is_some();
+			provenance.record(entry, entry.strategy() == Strategy::Merge && merged);
+		}
+
+		for path in changed.into_iter().filter(|path| sink.exists(path)) {
+			if let Some(record) = current.get(path).copied() {
+				let output = output_hash(sink, path)?;
+				current.insert(path.to_path_buf(), Record { output, ..record });
+			}
+		}
+
+		Ok((current, provenance))
+	}
+
+	/// Hash the content of the given source index
+	fn content_hash(&self, index: &Index) -> Result<u64, Error> {
+		let project = self
+			.projects
+			.get(index.pid())
+			.ok_or_else(|| Error::unknown_index(index.clone()))?;
+		let source = project.source();
+		let reader = source
+			.open(index.path())
+			.map_err(|error| Error::load(index.clone(), error))?;
+		read_hash(reader, index.path())
+	}
+
+	fn export_into<S: OutputSink>(&self, sink: &mut S) -> Result<Provenance, Error> {
+		let plan = self.plan()?;
+		let mapping = plan.mapping();
+		let mut provenance = Provenance::new();
+		self.export_entries(&mapping, &plan, sink, &mut provenance, abort)?;
+		Ok(provenance)
+	}
+
+	fn export_lenient<S: OutputSink>(&self, sink: &mut S) -> Result<ExportReport, Error> {
+		let plan = self.plan()?;
+		let mapping = plan.mapping();
+		let mut provenance = Provenance::new();
+		let mut failures = Vec::new();
+		self.export_entries(&mapping, &plan, sink, &mut provenance, |failure| {
+			debug!("{}, continue with the next index", failure);
+			failures.push(failure);
+			Ok(())
+		})?;
+		Ok(ExportReport::new(provenance, failures))
+	}
+
+	/// Export every entry, `on_failure` decide whether a failing entry abort the export
+	fn export_entries<'p, I, S, F>(
+		&'p self,
+		mapping: &'p IndexMapping<'p>,
+		entries: I,
+		sink: &mut S,
+		provenance: &mut Provenance,
+		mut on_failure: F,
+	) -> Result<(), Error>
+	where
+		I: IntoIterator<Item = &'p PlanEntry>,
+		S: OutputSink,
+		F: FnMut(ExportFailure) -> Result<(), Error>,
+	{
+		let mut exporter = self.exporter(sink, mapping);
+
+		for entry in entries {
+			if self.is_overwritten(entry) {
+				debug!("Skip copying {}, a higher priority copy replace it", entry.source());
+				continue;
+			}
+
+			if let Err((operation, error)) = export_entry(&mut exporter, entry, provenance) {
+				on_failure(ExportFailure::new(entry.source().clone(), operation, error))?;
+			}
+		}
+
+		for (path, contributors) in exporter.take_merged() {
+			match exporter.write_merged(contributors) {
+				Ok(relations) => provenance.relate(path, relations),
+				Err((index, operation, error)) => {
+					provenance.remove(path);
+					on_failure(ExportFailure::new(index.clone(), operation, error))?;
 				}
 			}
 		}
@@ -99,100 +496,286 @@ where
 	}
 
 	fn indexes(&self) -> impl Iterator<Item = (&Index, Strategy)> {
-		self.strategy().map(|(&a, &b)| (a, b))
+		self.strategy().map(|&(a, b)| (a, b))
+	}
+}
+
+#[cfg(feature = "parallel")]
+impl<'a, W> Timeline<'a, W>
+where
+	W: Workspace,
+	W::Project: Sync,
+{
+	/// Same as [export_to()](#method.export_to) but every output path is loaded, transformed and written on a thread pool.
+	///
+	/// Files that are merged into the same output are still handled one after another in priority order.
+	/// This require the `parallel` feature and projects that can be shared between threads.
+	pub fn export_to_parallel<P>(self, path: P) -> Result<Provenance, Error>
+	where
+		P: Into<PathBuf>,
+	{
+		stage(path.into(), |sink| self.export_parallel(sink))
+	}
+
+	/// Export every output path on its own thread, entries of the same output are still exported in order
+	fn export_parallel(&self, sink: &mut DirSink) -> Result<Provenance, Error> {
+		let plan = self.plan()?;
+		let mapping = plan.mapping();
+
+		let mut outputs: BTreeMap<&Path, Vec<&PlanEntry>> = BTreeMap::new();
+		for entry in &plan {
+			outputs
+				.entry(entry.output().path())
+				.or_default()
+				.push(entry);
+		}
+		debug!("Export {} outputs in parallel", outputs.len());
+
+		let outputs: Vec<_> = outputs.into_values().collect();
+		let provenances = outputs
+			.into_par_iter()
+			.map(|entries| {
+				let mut sink = sink.clone();
+				let mut provenance = Provenance::new();
+				self.export_entries(&mapping, entries, &mut sink, &mut provenance, abort)?;
+				Ok(provenance)
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+
+		let mut provenance = Provenance::new();
+		for other in provenances {
+			provenance.extend(other);
+		}
+		Ok(provenance)
 	}
 }
 
-/// A struct that handle communication with the filesystem.
+/// Export a single entry, the error is tagged with the operation that failed
+fn export_entry<'a, W, S>(
+	exporter: &mut Exporter<'a, '_, W, S>,
+	entry: &'a PlanEntry,
+	provenance: &mut Provenance,
+) -> Result<(), (Operation, Error)>
+where
+	W: Workspace,
+	S: OutputSink,
+{
+	let index = entry.source();
+	let strategy = entry.strategy();
+	debug!("Export {} with {:?}", index, strategy);
+
+	if strategy == Strategy::Replace && W::pass_through(index) {
+		exporter.copy(index)?;
+		provenance.record(entry, false);
+		return Ok(());
+	}
+
+	let file = exporter
+		.file(index)
+		.map_err(|error| (Operation::Load, error))?;
+
+	if strategy == Strategy::Merge {
+		let merged = exporter.merge(file, entry);
+		provenance.record(entry, merged);
+		return Ok(());
+	}
+
+	let relations = exporter
+		.write(file, index)
+		.map_err(|error| (Operation::Write, error))?;
+	provenance.record(entry, false);
+	provenance.relate(entry.output().path(), relations);
+	Ok(())
+}
+
+/// Abort the export on the first failure
+fn abort(failure: ExportFailure) -> Result<(), Error> {
+	Err(failure.into_error())
+}
+
+/// Export into a staging directory next to `target` and move it into place once `export` succeeded
+fn stage<T, F>(target: PathBuf, export: F) -> Result<T, Error>
+where
+	F: FnOnce(&mut DirSink) -> Result<T, Error>,
+{
+	let target = fs::resolve(&target)?;
+	let staging = fs::sibling(&target, "staging")?;
+	debug!("Stage export of {} in {}", target.display(), staging.display());
+
+	fs::clear_dir(&staging)?;
+	fs::create_dir_all(&staging)?;
+
+	match export(&mut DirSink::new(&staging)) {
+		Ok(result) => {
+			fs::replace_dir(&staging, &target)?;
+			Ok(result)
+		}
+		Err(error) => {
+			debug!("Export failed, roll back {}", staging.display());
+			if let Err(cleanup) = fs::clear_dir(&staging) {
+				warn!("Unable to remove staging directory {}: {}", staging.display(), cleanup);
+			}
+			Err(error)
+		}
+	}
+}
+
+/// Load the file at the given `index` from its project
+fn load_file<W: Workspace>(
+	projects: &HashMap<Pid, &W::Project>,
+	index: &Index,
+) -> Result<W::File, Error> {
+	let project = projects
+		.get(index.pid())
+		.ok_or_else(|| Error::unknown_index(index.clone()))?;
+	debug!("Looking up file with index {} in its project", index);
+	let source = project.source();
+	source
+		.open(index.path())
+		.and_then(|mut reader| W::file(index, &mut reader))
+		.map_err(|error| Error::load(index.clone(), error))
+}
+
+/// Maximum number of time an index will be renamed before giving up on finding an unused path
+const RENAME_ATTEMPTS: usize = 16;
+
+/// Rename the given `index` until its path does not collide with any path in `taken`.
 ///
-/// This is use to actually write the in-memory data into the filesystem.
-struct Exporter<'a, W> {
-	root: PathBuf,
-	output_id: Pid,
-	projects: HashMap<Pid, PathBuf>,
-	mapping: IndexMapping<'a>,
-	_workspace: std::marker::PhantomData<W>,
+/// `taken` should contain every index in the workspace and it will be updated with the new path.
+fn unique_rename<W: Workspace>(index: &Index, taken: &mut HashSet<PathBuf>) -> Result<Index, Error> {
+	let mut renamed = index.rename(W::formatter)?;
+
+	for _ in 0..RENAME_ATTEMPTS {
+		if taken.insert(renamed.path().to_path_buf()) {
+			return Ok(renamed);
+		}
+
+		debug!("Path {} is already taken, rename {} again", renamed.path().display(), index);
+		renamed = renamed.rename(W::formatter)?;
+	}
+
+	Err(Error::rename_collision(index.clone(), renamed.path()))
+}
+
+/// Hash the content of the output file at the given `path`
+fn output_hash<S: OutputSink>(sink: &S, path: &Path) -> Result<u64, Error> {
+	let reader = sink.read(path)?;
+	read_hash(reader, path)
+}
+
+fn read_hash(mut reader: impl Read, path: &Path) -> Result<u64, Error> {
+	let mut data = Vec::new();
+	reader
+		.read_to_end(&mut data)
+		.map_err(|io| Error::read(path, io))?;
+	Ok(manifest::hash(&data))
 }
 
-impl<'a, W> Exporter<'a, W>
+/// A struct that handle communication between the projects and the output sink.
+///
+/// This is use to actually write the in-memory data into the [OutputSink](../sink/trait.OutputSink.html).
+/// Merged files are kept in memory until every contributor is loaded so that each of them is merged and written only once.
+struct Exporter<'a, 's, W, S>
+where
+	W: Workspace,
+{
+	sink: &'s mut S,
+	projects: &'a HashMap<Pid, &'a W::Project>,
+	mapping: &'a IndexMapping<'a>,
+	/// Every loaded contributor of every merged output, in export order
+	merged: BTreeMap<&'a Path, Vec<(&'a Index, W::File)>>,
+}
+
+impl<'a, 's, W, S> Exporter<'a, 's, W, S>
 where
 	W: Workspace,
+	S: OutputSink,
 {
 	fn new(
-		root: impl Into<PathBuf>,
-		output_id: Pid,
-		projects: HashMap<Pid, PathBuf>,
-		mapping: IndexMapping<'a>,
+		sink: &'s mut S,
+		projects: &'a HashMap<Pid, &'a W::Project>,
+		mapping: &'a IndexMapping<'a>,
 	) -> Self {
-		let root = root.into();
-
-		debug!(
-			"Create exporter to {} with pid {}",
-			root.display(),
-			output_id
-		);
-
 		Self {
-			root,
-			output_id,
+			sink,
 			projects,
 			mapping,
-			_workspace: std::marker::PhantomData,
+			merged: BTreeMap::new(),
 		}
 	}
 
-	fn file(&self, index: &Index) -> Option<W::File> {
-		let pid = index.pid();
-		let root = self.projects.get(pid)?;
-		let path = index.prefix(root);
-		debug!(
-			"Looking up file with index {} at path {}",
-			index,
-			path.display()
-		);
-		W::file(&path, *pid)
-	}
-
-	fn path(&self, index: &Index) -> PathBuf {
-		index.prefix(&self.root)
+	fn file(&self, index: &Index) -> Result<W::File, Error> {
+		load_file::<W>(self.projects, index)
 	}
 
-	fn index(&'a self, index: &'a Index) -> Result<&'a Index, Error> {
+	fn index(&self, index: &'a Index) -> Result<&Index, Error> {
 		self.mapping
 			.get(index)
 			.ok_or_else(|| Error::unknown_index(index.clone()))
 	}
 
-	fn write(&self, file: W::File, index: &Index) -> Result<(), Error> {
+	/// Write the file into the output and return its relations after renaming
+	fn write(&mut self, file: W::File, index: &'a Index) -> Result<Vec<Index>, Error> {
 		let output_index = self.index(index)?;
-		let path = self.path(output_index);
+		let path = output_index.path().to_path_buf();
 
-		let file = self.mapping.apply_mapping(file);
-		let content = file.data();
+		let with_index = |error| Error::file(index.clone(), error);
+		let file = self.mapping.apply_mapping(file).map_err(with_index)?;
+		let relations = file.relation().into_iter().map(Relation::index).collect();
+		let content = file.data().map_err(with_index)?;
 
 		debug!("Write file content from {} to {}", index, path.display());
-		fs::prepare_parent(&path)?;
-		fs::write(path, content)?;
-		Ok(())
+		self.sink.write(&path, &content)?;
+		Ok(relations)
 	}
 
-	/// Merge Index
-	fn merge(&self, file: W::File, index: &Index) -> Result<(), Error> {
-		let output_index = index.with_pid(self.output_id);
-		debug!("Try to merge file's content from {} with {}", index, output_index);
-		let file = match self.file(&output_index) {
-			Some(conflict) => conflict.merge(file)?,
-			None => file,
-		};
-		self.write(file, index)
+	/// Stream the file straight from its project into the output without loading it
+	fn copy(&mut self, index: &'a Index) -> Result<(), (Operation, Error)> {
+		let output_index = self.index(index).map_err(|error| (Operation::Write, error))?;
+		let path = output_index.path().to_path_buf();
+
+		let project = self
+			.projects
+			.get(index.pid())
+			.ok_or_else(|| (Operation::Load, Error::unknown_index(index.clone())))?;
+		let source = project.source();
+		let mut reader = source
+			.open(index.path())
+			.map_err(|error| (Operation::Load, Error::load(index.clone(), error)))?;
+
+		debug!("Copy file content from {} to {}", index, path.display());
+		self.sink
+			.write_from(&path, &mut reader)
+			.map_err(|error| (Operation::Write, error))
+	}
+
+	/// Keep the file until every contributor of the same output is loaded.
+	///
+	/// Return whether a previous contributor was already loaded.
+	fn merge(&mut self, file: W::File, entry: &'a PlanEntry) -> bool {
+		let contributors = self.merged.entry(entry.output().path()).or_default();
+		contributors.push((entry.source(), file));
+		contributors.len() > 1
 	}
 
-	fn exist(&self, index: &Index) -> bool {
-		self.path(index).exists()
+	/// Merge every contributor with [File::merge_all()](../file/trait.File.html#method.merge_all) and write the result.
+	///
+	/// On error, return the contributor that the failure is reported for along with the failed operation.
+	fn write_merged(
+		&mut self,
+		contributors: Vec<(&'a Index, W::File)>,
+	) -> Result<Vec<Index>, (&'a Index, Operation, Error)> {
+		let (indexes, files): (Vec<_>, Vec<_>) = contributors.into_iter().unzip();
+		let index = indexes[indexes.len() - 1];
+		debug!("Merge {} contributors into {}", files.len(), index);
+
+		let file = W::File::merge_all(files).map_err(|error| (index, Operation::Merge, error))?;
+		self.write(file, index)
+			.map_err(|error| (index, Operation::Write, error))
 	}
 
-	fn exist_in_output(&self, index: &Index) -> bool {
-		let output_index = index.with_pid(self.output_id);
-		self.exist(&output_index)
+	/// Take every contributor of every merged output, sorted by output path
+	fn take_merged(&mut self) -> BTreeMap<&'a Path, Vec<(&'a Index, W::File)>> {
+		std::mem::take(&mut self.merged)
 	}
 }
@@ -1,6 +1,8 @@
-use super::prelude::{File, Index, IndexList, Pid, Project, Strategy, Timeline};
+use super::conflict::{Conflict, ConflictReport};
+use super::prelude::{ConflictResolver, Error, File, Index, Pid, Project, Strategy, Timeline};
 use log::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
 use std::path::Path;
 
 /// Workspace interface
@@ -10,13 +12,64 @@ pub trait Workspace {
 
 	fn projects(&self) -> &[Self::Project];
 
+	/// Project's priority, from the highest to the lowest.
+	///
+	/// Projects are exported from the lowest priority to the highest so the file from a higher priority project
+	/// will win under [Strategy::Replace](../project/enum.Strategy.html#variant.Replace) and will be passed as the newer file in [File::merge()](../file/trait.File.html#method.merge).
+	/// Projects missing from this list are treated as lower priority than every listed project.
+	///
+	/// By default, this follow the order of [projects()](#tymethod.projects).
+	fn priority(&self) -> Vec<Pid> {
+		self.projects().iter().map(Project::pid).collect()
+	}
+
 	/// Conflict handling strategy
 	///
 	/// Note that the strategy should not be determine from the content of the file but rather the *location* of the file.
 	/// This is for keeping the handling strategy consistent across all project.
+	/// Use [content_strategy()](#method.content_strategy) for conflicts that depend on the content.
+	///
+	/// This is called once for every conflicting path with the index from the highest priority project.
 	fn strategy(&self, index: &Index) -> Strategy;
 
-	fn file(path: &Path, pid: Pid) -> Option<Self::File>;
+	/// Content-aware conflict handling strategy
+	///
+	/// This is called once for every conflicting path that [content_aware()](#method.content_aware) opt in, with the loaded file of every conflicting index,
+	/// from the lowest priority project to the highest.
+	/// Returning `None` fall back to [strategy()](#tymethod.strategy), which is also used when any of the files can not be loaded.
+	///
+	/// By default, this always fall back to [strategy()](#tymethod.strategy).
+	fn content_strategy(&self, _files: Vec<(&Index, Self::File)>) -> Option<Strategy> {
+		None
+	}
+
+	/// Whether the conflicting files at the given `index` should be loaded and handed to [content_strategy()](#method.content_strategy).
+	///
+	/// This is called with the index from the highest priority project, paths where [pass_through()](#method.pass_through) return `true` are never loaded.
+	///
+	/// By default, no file is loaded while resolving the workspace.
+	fn content_aware(&self, _index: &Index) -> bool {
+		false
+	}
+
+	/// Load the file at the given `index` from `reader`.
+	///
+	/// The index's path is relative to its project and can be used to determine the file type.
+	/// The reader always come from the original project, merged files are combined in memory before being written to the output.
+	///
+	/// Any error abort the export with [Error::Load](../prelude/enum.Error.html#variant.Load) that carry the index of the file.
+	fn file(index: &Index, reader: &mut dyn Read) -> Result<Self::File, Error>;
+
+	/// Whether the file at the given `index` can be copied into the output without being loaded.
+	///
+	/// This is only used for files under [Strategy::Replace](../project/enum.Strategy.html#variant.Replace), which are streamed
+	/// straight from their project into the output instead of going through [file()](#tymethod.file) and [File::data()](../file/trait.File.html#tymethod.data).
+	/// Only return `true` for files that never contain any relation (e.g. textures or sounds) since their content is left untouched.
+	///
+	/// By default, every file is loaded.
+	fn pass_through(_index: &Index) -> bool {
+		false
+	}
 
 	fn formatter(pid: &Pid, filename: &str) -> String {
 		let result = format!("{}_{}", filename, pid.value());
@@ -27,56 +80,171 @@ pub trait Workspace {
 		result
 	}
 
-	fn resolve(&self) -> Timeline<Self>
+	fn resolve(&self) -> Timeline<'_, Self>
+	where
+		Self: Sized,
+	{
+		timeline(self, |_| None)
+	}
+
+	/// Same as [resolve()](#method.resolve) but consult the given `resolver` for every conflict.
+	///
+	/// The strategy returned by the resolver take precedence over [strategy()](#tymethod.strategy) and [content_strategy()](#method.content_strategy).
+	fn resolve_with<R>(&self, resolver: &mut R) -> Timeline<'_, Self>
 	where
 		Self: Sized,
+		R: ConflictResolver<Self::Project>,
 	{
-		let preview = preview(self);
-		debug!("Generated index preview");
-		let projects = project_paths(self);
-		debug!("Generated mapping between Pid and Project Path");
-
-		let strategy = indexes(self)
-			.map(|index| {
-				let strategy = match preview.get_different_pid(index) {
-					Some(conflict) => {
-						let strategy = self.strategy(index);
-						debug!(
-							"Found conflicting index at {}, choose {:?} for index: {}",
-							conflict, strategy, index
-						);
-						strategy
-					}
-					None => {
-						debug!("No conflicting index found, choose {:?} for index: {}", Strategy::Replace, index);
-						Strategy::Replace
-					}
-				};
-
-				(index, strategy)
-			})
-			.collect();
-
-		Timeline::new(strategy, projects)
+		let projects = project_map(self);
+		timeline(self, |conflict| {
+			let projects: Vec<_> = conflict
+				.pids()
+				.filter_map(|pid| projects.get(pid).copied())
+				.collect();
+			resolver.choose(conflict, &projects)
+		})
 	}
 }
 
+/// Resolve the workspace into a timeline, `choose` may override the strategy of every conflict
+fn timeline<W, F>(workspace: &W, choose: F) -> Timeline<'_, W>
+where
+	W: Workspace,
+	F: FnMut(&Conflict) -> Option<Strategy>,
+{
+	let projects = project_map(workspace);
+	debug!("Generated mapping between Pid and Project");
+	let priority = priority(workspace);
+	debug!("Generated project priority: {:?}", priority);
+	let indexes = ordered_indexes(workspace, &priority);
+	let conflicts = conflicts(workspace, &indexes, choose);
+	debug!("Found {} conflicting paths", conflicts.len());
+
+	let strategy = indexes
+		.into_iter()
+		.map(|index| {
+			let strategy = match conflicts.get(index.path()) {
+				Some(conflict) => {
+					debug!(
+						"Found conflicting index at {}, choose {:?} for index: {}",
+						conflict.path().display(),
+						conflict.strategy(),
+						index
+					);
+					conflict.strategy()
+				}
+				None => {
+					debug!("No conflicting index found, choose {:?} for index: {}", Strategy::Replace, index);
+					Strategy::Replace
+				}
+			};
+
+			(index, strategy)
+		})
+		.collect();
+
+	Timeline::new(strategy, projects, priority, conflicts)
+}
+
 /// Get an iterator over Workspace's projects
 fn projects<W: Workspace>(workspace: &W) -> impl Iterator<Item = &W::Project> {
 	workspace.projects().iter()
 }
 
-/// Get an iterator over every indexes in all projects
-fn indexes<W: Workspace>(workspace: &W) -> impl Iterator<Item = &Index> {
-	projects(workspace).flat_map(Project::indexes)
+/// Get every project's Pid from the highest priority to the lowest
+///
+/// Only the first occurrence of a duplicated Pid is kept.
+fn priority<W: Workspace>(workspace: &W) -> Vec<Pid> {
+	let listed = workspace.priority().into_iter();
+	let missing = projects(workspace).map(Project::pid);
+
+	let mut priority = Vec::new();
+	for pid in listed.chain(missing) {
+		if !priority.contains(&pid) {
+			priority.push(pid);
+		}
+	}
+	priority
+}
+
+/// Get every indexes in export order, from the lowest priority project to the highest and sorted by path within each project
+fn ordered_indexes<'a, W: Workspace>(workspace: &'a W, priority: &[Pid]) -> Vec<&'a Index> {
+	let projects = project_map(workspace);
+
+	priority
+		.iter()
+		.rev()
+		.filter_map(|pid| projects.get(pid))
+		.flat_map(|project| {
+			let mut indexes: Vec<_> = project.indexes().into_iter().collect();
+			indexes.sort_by(|a, b| a.path().cmp(b.path()));
+			indexes
+		})
+		.collect()
 }
 
-/// Get HashMap of 'Pid → Project's path'
-fn project_paths<W: Workspace>(workspace: &W) -> HashMap<Pid, &Path> {
-	projects(workspace).map(|p| (p.pid(), p.root())).collect()
+/// Get HashMap of 'Pid → Project'
+fn project_map<W: Workspace>(workspace: &W) -> HashMap<Pid, &W::Project> {
+	projects(workspace).map(|p| (p.pid(), p)).collect()
 }
 
-/// Get IndexList of all indexes
-fn preview<W: Workspace>(workspace: &W) -> IndexList {
-	indexes(workspace).collect()
+/// Group indexes by path and choose a strategy for every path that is shared by multiple projects
+fn conflicts<W, F>(workspace: &W, indexes: &[&Index], mut choose: F) -> ConflictReport
+where
+	W: Workspace,
+	F: FnMut(&Conflict) -> Option<Strategy>,
+{
+	let mut groups: BTreeMap<&Path, Vec<Index>> = BTreeMap::new();
+	for &index in indexes {
+		groups.entry(index.path()).or_default().push(index.clone());
+	}
+
+	groups
+		.into_iter()
+		.filter(|(_, group)| group.len() > 1)
+		.map(|(path, group)| {
+			let index = &group[group.len() - 1];
+			let files = if !group.iter().any(W::pass_through) && workspace.content_aware(index) {
+				load_group(workspace, &group)
+			} else {
+				None
+			};
+			let strategy = files
+				.and_then(|files| workspace.content_strategy(files))
+				.unwrap_or_else(|| workspace.strategy(index));
+			let conflict = Conflict::new(path, group, strategy);
+			match choose(&conflict) {
+				Some(strategy) => {
+					debug!("Resolver choose {:?} for {}", strategy, path.display());
+					Conflict::new(path, conflict.indexes().to_vec(), strategy)
+				}
+				None => conflict,
+			}
+		})
+		.collect()
+}
+
+/// Load every file in the given conflicting `group`, return `None` if any of them can not be loaded
+fn load_group<'a, W: Workspace>(
+	workspace: &W,
+	group: &'a [Index],
+) -> Option<Vec<(&'a Index, W::File)>> {
+	let projects = project_map(workspace);
+
+	group
+		.iter()
+		.map(|index| {
+			let source = projects.get(index.pid())?.source();
+			let file = source
+				.open(index.path())
+				.and_then(|mut reader| W::file(index, &mut reader));
+			match file {
+				Ok(file) => Some((index, file)),
+				Err(error) => {
+					debug!("Unable to load {}: {}, fall back to location based strategy", index, error);
+					None
+				}
+			}
+		})
+		.collect()
 }
@@ -0,0 +1,869 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use superfusion::prelude::*;
+use superfusion::project::SourceProject;
+use superfusion::report::Operation;
+use superfusion::resolver::Decisions;
+use superfusion::sink::MemorySink;
+use superfusion::source::DirSource;
+
+#[derive(Debug, thiserror::Error)]
+#[error("refuse to merge conflicting file")]
+struct MergeError;
+
+#[derive(Debug, thiserror::Error)]
+#[error("refuse to serialize unserializable file")]
+struct DataError;
+
+/// Plain text file where every line starting with `@` is a relation to another file
+struct Text {
+	pid: Pid,
+	data: String,
+}
+
+impl File for Text {
+	fn relation(&self) -> Vec<Relation> {
+		self.data
+			.lines()
+			.filter_map(|line| line.strip_prefix('@'))
+			.map(|path| Relation::new(Index::new(self.pid, path)))
+			.collect()
+	}
+	fn data(self) -> Result<Vec<u8>, Error> {
+		if self.data.contains("unserializable") {
+			return Err(Error::custom(DataError));
+		}
+
+		Ok(self.data.into_bytes())
+	}
+	fn modify_relation(mut self, from: &Index, to: &Index) -> Result<Self, Error> {
+		let from = format!("@{}", from.path().display());
+		let to = format!("@{}", to.path().display());
+		self.data = self
+			.data
+			.lines()
+			.map(|line| if line == from { &to } else { line })
+			.map(|line| format!("{}\n", line))
+			.collect();
+		Ok(self)
+	}
+	fn merge(self, other: Self) -> Result<Self, Error> {
+		if self.data.contains("conflict") || other.data.contains("conflict") {
+			return Err(Error::custom(MergeError));
+		}
+
+		let data = self.data + &other.data;
+		Ok(Text { data, ..other })
+	}
+	/// Union of every line where a line starting with `-` remove that line from the lower priority copies
+	fn merge_all(files: Vec<Self>) -> Result<Self, Error> {
+		if !files.iter().any(|file| file.data.contains("-")) {
+			let mut files = files.into_iter();
+			let first = files.next().unwrap();
+			return files.try_fold(first, Self::merge);
+		}
+
+		let pid = files[files.len() - 1].pid;
+		let mut lines: Vec<&str> = Vec::new();
+		for file in &files {
+			for line in file.data.lines() {
+				match line.strip_prefix('-') {
+					Some(removed) => lines.retain(|&line| line != removed),
+					None => lines.push(line),
+				}
+			}
+		}
+
+		let data = lines.iter().map(|line| format!("{}\n", line)).collect();
+		Ok(Text { pid, data })
+	}
+}
+
+struct TestWorkspace<P = SourceProject<DirSource>> {
+	projects: Vec<P>,
+	priority: Vec<Pid>,
+	content_aware: bool,
+}
+
+impl TestWorkspace {
+	fn new(root: impl AsRef<Path>) -> Self {
+		let mut paths: Vec<_> = std::fs::read_dir(root)
+			.unwrap()
+			.filter_map(|entry| entry.ok())
+			.map(|entry| entry.path())
+			.collect();
+		paths.sort();
+
+		let projects = paths
+			.into_iter()
+			.enumerate()
+			.map(|(i, path)| {
+				let source = DirSource::new(&path);
+				SourceProject::new(path, Pid::new(i), source).unwrap()
+			})
+			.collect();
+
+		Self {
+			projects,
+			priority: Vec::new(),
+			content_aware: true,
+		}
+	}
+}
+
+impl<P: Project> Workspace for TestWorkspace<P> {
+	type Project = P;
+	type File = Text;
+
+	fn projects(&self) -> &[Self::Project] {
+		&self.projects
+	}
+
+	fn priority(&self) -> Vec<Pid> {
+		self.priority.clone()
+	}
+
+	fn strategy(&self, index: &Index) -> Strategy {
+		let extension = index.path().extension().and_then(|e| e.to_str());
+		if index.path().ends_with("pack.mcmeta") || extension == Some("png") {
+			Strategy::Replace
+		} else if index.path().ends_with("bar.txt") {
+			Strategy::RenameOthers
+		} else if index.path().ends_with("first.txt") {
+			Strategy::KeepFirst
+		} else if index.path().ends_with("last.txt") {
+			Strategy::KeepLast
+		} else if index.path().ends_with("skip.txt") {
+			Strategy::Skip
+		} else if index.path().ends_with("protected.txt") {
+			Strategy::Fail
+		} else if extension == Some("json") {
+			Strategy::Rename
+		} else {
+			Strategy::Merge
+		}
+	}
+
+	fn pass_through(index: &Index) -> bool {
+		index.path().extension().and_then(|e| e.to_str()) == Some("png")
+	}
+
+	fn content_aware(&self, index: &Index) -> bool {
+		let extension = index.path().extension().and_then(|e| e.to_str());
+		self.content_aware && extension == Some("json")
+	}
+
+	fn content_strategy(&self, files: Vec<(&Index, Self::File)>) -> Option<Strategy> {
+		let (_, first) = &files[0];
+		if files.iter().all(|(_, file)| file.data == first.data) {
+			Some(Strategy::KeepFirst)
+		} else {
+			None
+		}
+	}
+
+	fn file(index: &Index, reader: &mut dyn Read) -> Result<Self::File, Error> {
+		let mut data = String::new();
+		reader
+			.read_to_string(&mut data)
+			.map_err(|io| Error::read(index.path(), io))?;
+		let pid = *index.pid();
+		Ok(Text { pid, data })
+	}
+}
+
+/// Resolver that keep the highest priority copy of every conflict and count how many time it was asked
+#[derive(Default)]
+struct Prompt {
+	asked: usize,
+}
+
+impl<P: Project> ConflictResolver<P> for Prompt {
+	fn choose(&mut self, conflict: &Conflict, projects: &[&P]) -> Option<Strategy> {
+		assert_eq!(conflict.indexes().len(), projects.len());
+		self.asked += 1;
+		Some(Strategy::KeepFirst)
+	}
+}
+
+/// Sink that count how many time every file was written
+#[derive(Default)]
+struct CountingSink {
+	inner: MemorySink,
+	writes: HashMap<PathBuf, usize>,
+}
+
+impl OutputSink for CountingSink {
+	fn exists(&self, path: &Path) -> bool {
+		self.inner.exists(path)
+	}
+
+	fn write(&mut self, path: &Path, data: &[u8]) -> Result<(), Error> {
+		*self.writes.entry(path.to_path_buf()).or_default() += 1;
+		self.inner.write(path, data)
+	}
+
+	fn read(&self, path: &Path) -> Result<Box<dyn Read + '_>, Error> {
+		self.inner.read(path)
+	}
+}
+
+fn output_dir(name: &str) -> PathBuf {
+	let path = std::env::temp_dir().join("superfusion-tests").join(name);
+	if path.exists() {
+		std::fs::remove_dir_all(&path).unwrap();
+	}
+	std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+	path
+}
+
+#[test]
+fn export_valid_workspace() {
+	let output = output_dir("export_valid_workspace");
+	let workspace = TestWorkspace::new("tests/export/valid");
+	workspace.resolve().export_to(&output).unwrap();
+
+	let list = std::fs::read_to_string(output.join("data/list.txt")).unwrap();
+	assert!(list.contains("one"));
+	assert!(list.contains("two"));
+	assert!(output.join("data/alpha.txt").is_file());
+	assert!(output.join("pack.mcmeta").is_file());
+}
+
+#[test]
+fn export_follow_project_priority() {
+	let mut sink = MemorySink::new();
+	let workspace = TestWorkspace::new("tests/export/valid");
+	workspace.resolve().export_with(&mut sink).unwrap();
+	assert_eq!(sink.get("data/list.txt"), Some(&b"two\none\n"[..]));
+	assert_eq!(sink.get("pack.mcmeta"), Some(&b"alpha\n"[..]));
+
+	let mut sink = MemorySink::new();
+	let mut workspace = TestWorkspace::new("tests/export/valid");
+	workspace.priority = vec![Pid::new(1), Pid::new(0)];
+	workspace.resolve().export_with(&mut sink).unwrap();
+	assert_eq!(sink.get("data/list.txt"), Some(&b"one\ntwo\n"[..]));
+	assert_eq!(sink.get("pack.mcmeta"), Some(&b"beta\n"[..]));
+}
+
+#[test]
+fn duplicated_priority_is_ignored() {
+	let mut sink = MemorySink::new();
+	let mut workspace = TestWorkspace::new("tests/export/valid");
+	workspace.priority = vec![Pid::new(0), Pid::new(0)];
+	workspace.resolve().export_with(&mut sink).unwrap();
+	assert_eq!(sink.get("data/alpha.txt"), Some(&b"alpha\n"[..]));
+	assert_eq!(sink.get("data/list.txt"), Some(&b"two\none\n"[..]));
+	assert_eq!(sink.get("pack.mcmeta"), Some(&b"alpha\n"[..]));
+}
+
+#[test]
+fn export_return_provenance() {
+	let output = output_dir("export_return_provenance");
+	let workspace = TestWorkspace::new("tests/export/valid");
+	let provenance = workspace
+		.resolve()
+		.export_with(&mut MemorySink::new())
+		.unwrap();
+	assert_eq!(provenance.len(), 3);
+
+	let list = provenance.get("data/list.txt").unwrap();
+	let sources: Vec<_> = list.sources().iter().map(|o| *o.source().pid()).collect();
+	assert_eq!(sources, vec![Pid::new(1), Pid::new(0)]);
+	assert!(list
+		.sources()
+		.iter()
+		.all(|o| o.strategy() == Strategy::Merge));
+
+	let pack = provenance.get("pack.mcmeta").unwrap();
+	assert_eq!(pack.sources().len(), 1);
+	assert_eq!(
+		pack.sources()[0].source(),
+		&Index::new(Pid::new(0), "pack.mcmeta")
+	);
+	assert!(!pack.sources()[0].renamed());
+
+	let file = output.with_extension("tsv");
+	provenance.save(&file).unwrap();
+	let content = std::fs::read_to_string(file).unwrap();
+	assert_eq!(content.lines().count(), 5);
+	assert!(content.contains("data/list.txt\t1\tdata/list.txt\tMerge\tfalse"));
+}
+
+#[test]
+fn resolve_report_conflicts() {
+	let workspace = TestWorkspace::new("tests/export/valid");
+	let timeline = workspace.resolve();
+	let conflicts = timeline.conflicts();
+
+	let paths: Vec<_> = conflicts.iter().map(Conflict::path).collect();
+	assert_eq!(
+		paths,
+		vec![Path::new("data/list.txt"), Path::new("pack.mcmeta")]
+	);
+
+	let list = conflicts.get("data/list.txt").unwrap();
+	assert_eq!(list.strategy(), Strategy::Merge);
+	let pids: Vec<_> = list.pids().copied().collect();
+	assert_eq!(pids, vec![Pid::new(1), Pid::new(0)]);
+	assert_eq!(
+		conflicts.get("pack.mcmeta").unwrap().strategy(),
+		Strategy::Replace
+	);
+	assert!(conflicts.get("data/alpha.txt").is_none());
+}
+
+#[test]
+fn plan_group_merged_outputs() {
+	let workspace = TestWorkspace::new("tests/export/valid");
+	let plan = workspace.resolve().plan().unwrap();
+	assert_eq!(plan.len(), 5);
+
+	let groups: Vec<_> = plan
+		.groups()
+		.into_iter()
+		.map(|(output, sources)| (output.path(), sources.len()))
+		.collect();
+	assert_eq!(
+		groups,
+		vec![
+			(Path::new("data/alpha.txt"), 1),
+			(Path::new("data/list.txt"), 2),
+			(Path::new("pack.mcmeta"), 2),
+		]
+	);
+
+	let merges = plan.merges();
+	assert_eq!(merges.len(), 1);
+	assert_eq!(merges[0].0.path(), Path::new("data/list.txt"));
+	let pids: Vec<_> = merges[0].1.iter().map(|index| *index.pid()).collect();
+	assert_eq!(pids, vec![Pid::new(1), Pid::new(0)]);
+}
+
+#[test]
+fn rename_avoid_existing_path() {
+	let workspace = TestWorkspace::new("tests/export/rename");
+	let plan = workspace.resolve().plan().unwrap();
+
+	let mut outputs: Vec<_> = plan.iter().map(|entry| entry.output().path()).collect();
+	outputs.sort();
+	let expect = vec![
+		Path::new("data/foo_0.json"),
+		Path::new("data/foo_1.json"),
+		Path::new("data/foo_1_1.json"),
+	];
+	assert_eq!(outputs, expect);
+
+	let beta = Index::new(Pid::new(1), "data/foo.json");
+	let entry = plan.iter().find(|entry| entry.source() == &beta).unwrap();
+	assert_eq!(entry.output().path(), Path::new("data/foo_1_1.json"));
+}
+
+#[test]
+fn rename_others_keep_highest_priority() {
+	let mut sink = MemorySink::new();
+	let workspace = TestWorkspace::new("tests/export/keep");
+	workspace.resolve().export_with(&mut sink).unwrap();
+
+	assert_eq!(sink.get("data/bar.txt"), Some(&b"alpha\n"[..]));
+	assert_eq!(sink.get("data/bar_1.txt"), Some(&b"beta\n"[..]));
+	assert_eq!(sink.get("data/bar_2.txt"), Some(&b"gamma\n"[..]));
+	assert_eq!(sink.len(), 3);
+}
+
+#[test]
+fn keep_or_skip_conflicting_files() {
+	let mut sink = MemorySink::new();
+	let workspace = TestWorkspace::new("tests/export/strategies");
+	let plan = workspace.resolve().plan().unwrap();
+	assert_eq!(plan.len(), 3);
+	assert_eq!(plan.dropped().len(), 7);
+	let first = Index::new(Pid::new(1), "data/first.txt");
+	assert!(plan.dropped().contains(&(first, Strategy::KeepFirst)));
+	let skip = Index::new(Pid::new(0), "data/skip.txt");
+	assert!(plan.dropped().contains(&(skip, Strategy::Skip)));
+	assert!(plan.iter().all(|entry| entry.strategy() != Strategy::Skip));
+
+	workspace.resolve().export_with(&mut sink).unwrap();
+	assert_eq!(sink.get("data/first.txt"), Some(&b"alpha\n"[..]));
+	assert_eq!(sink.get("data/last.txt"), Some(&b"gamma\n"[..]));
+	assert_eq!(sink.get("data/only.txt"), Some(&b"alpha\n"[..]));
+	assert_eq!(sink.get("data/skip.txt"), None);
+	assert_eq!(sink.len(), 3);
+}
+
+#[test]
+fn fail_on_protected_conflict() {
+	let workspace = TestWorkspace::new("tests/export/protected");
+	let error = workspace.resolve().plan().unwrap_err();
+	match error {
+		Error::Conflict { path, indexes } => {
+			assert_eq!(path, Path::new("protected.txt"));
+			assert_eq!(indexes.len(), 2);
+		}
+		error => panic!("Unexpected error: {}", error),
+	}
+
+	let mut sink = MemorySink::new();
+	assert!(workspace.resolve().export_with(&mut sink).is_err());
+	assert!(sink.is_empty());
+}
+
+#[test]
+fn content_strategy_override_location() {
+	let mut sink = MemorySink::new();
+	let workspace = TestWorkspace::new("tests/export/identical");
+	let timeline = workspace.resolve();
+	let conflicts = timeline.conflicts();
+	assert_eq!(
+		conflicts.get("data/same.json").map(Conflict::strategy),
+		Some(Strategy::KeepFirst)
+	);
+	assert_eq!(
+		conflicts.get("data/diff.json").map(Conflict::strategy),
+		Some(Strategy::Rename)
+	);
+
+	timeline.export_with(&mut sink).unwrap();
+	assert_eq!(sink.get("data/same.json"), Some(&b"same\n"[..]));
+	assert_eq!(sink.get("data/diff_0.json"), Some(&b"alpha\n"[..]));
+	assert_eq!(sink.get("data/diff_1.json"), Some(&b"beta\n"[..]));
+	assert_eq!(sink.len(), 3);
+}
+
+#[test]
+fn content_strategy_require_opt_in() {
+	let mut sink = MemorySink::new();
+	let mut workspace = TestWorkspace::new("tests/export/identical");
+	workspace.content_aware = false;
+	let timeline = workspace.resolve();
+	assert_eq!(
+		timeline.conflicts().get("data/same.json").map(Conflict::strategy),
+		Some(Strategy::Rename)
+	);
+
+	timeline.export_with(&mut sink).unwrap();
+	assert_eq!(sink.get("data/same_0.json"), Some(&b"same\n"[..]));
+	assert_eq!(sink.get("data/same_1.json"), Some(&b"same\n"[..]));
+	assert_eq!(sink.len(), 4);
+}
+
+#[test]
+fn replay_recorded_decisions() {
+	let output = output_dir("replay_recorded_decisions").join("decisions.tsv");
+	let workspace = TestWorkspace::new("tests/export/valid");
+
+	let mut decisions = Decisions::new();
+	let mut sink = MemorySink::new();
+	let mut recorder = decisions.record(Prompt::default());
+	let timeline = workspace.resolve_with(&mut recorder);
+	assert_eq!(recorder.into_inner().asked, 2);
+	timeline.export_with(&mut sink).unwrap();
+	assert_eq!(sink.get("data/list.txt"), Some(&b"one\n"[..]));
+	decisions.save(&output).unwrap();
+
+	let mut decisions = Decisions::load(&output).unwrap();
+	assert_eq!(decisions.len(), 2);
+	let mut recorder = decisions.record(Prompt::default());
+	let timeline = workspace.resolve_with(&mut recorder);
+	assert_eq!(recorder.into_inner().asked, 0);
+	let list = timeline.conflicts().get("data/list.txt").unwrap();
+	assert_eq!(list.strategy(), Strategy::KeepFirst);
+}
+
+#[test]
+fn detect_dangling_relations() {
+	let workspace = TestWorkspace::new("tests/export/dangling");
+	let timeline = workspace.resolve();
+	let reference = Index::new(Pid::new(0), "data/ref.txt");
+	let missing = Index::new(Pid::new(0), "data/missing.txt");
+
+	let graph = timeline.relations().unwrap();
+	assert_eq!(graph.dependents(&missing), vec![&reference]);
+
+	let dangling = timeline.dangling().unwrap();
+	assert_eq!(dangling.len(), 1);
+	assert_eq!(dangling[0].from(), &reference);
+	assert_eq!(dangling[0].to(), &missing);
+
+	let provenance = timeline.export_with(&mut MemorySink::new()).unwrap();
+	let dangling = provenance.dangling();
+	assert_eq!(dangling.len(), 1);
+	assert_eq!(dangling[0].from().path(), Path::new("data/ref.txt"));
+	assert_eq!(dangling[0].to().path(), Path::new("data/missing.txt"));
+}
+
+#[test]
+fn tree_shake_drop_unreachable_files() {
+	let workspace = TestWorkspace::new("tests/export/shake");
+	let timeline = workspace
+		.resolve()
+		.tree_shake(|index| index.path() == Path::new("root.txt"))
+		.unwrap();
+
+	let mut sink = MemorySink::new();
+	timeline.export_with(&mut sink).unwrap();
+
+	let paths: Vec<_> = sink.iter().map(|(path, _)| path.as_path()).collect();
+	let expected = [
+		"data/deep.txt",
+		"data/extra.txt",
+		"data/used.txt",
+		"root.txt",
+	];
+	assert_eq!(paths, expected.iter().map(Path::new).collect::<Vec<_>>());
+	assert_eq!(
+		sink.get("data/used.txt"),
+		Some(&b"beta\n@data/extra.txt\nalpha\n@data/deep.txt\n"[..])
+	);
+}
+
+#[test]
+fn failed_export_keep_previous_output() {
+	let output = output_dir("failed_export_keep_previous_output");
+	std::fs::create_dir_all(&output).unwrap();
+	std::fs::write(output.join("previous.txt"), "previous").unwrap();
+
+	let workspace = TestWorkspace::new("tests/export/broken");
+	let result = workspace.resolve().export_to(&output);
+	assert!(result.is_err());
+
+	let entries: Vec<_> = std::fs::read_dir(&output).unwrap().collect();
+	assert_eq!(entries.len(), 1);
+	assert!(output.join("previous.txt").is_file());
+
+	let staging = output.with_file_name(".failed_export_keep_previous_output.staging");
+	assert!(!staging.exists());
+}
+
+#[test]
+fn export_to_resolve_parent_dir() {
+	let output = output_dir("export_to_resolve_parent_dir");
+	std::fs::create_dir_all(output.join("sub")).unwrap();
+	let workspace = TestWorkspace::new("tests/export/valid");
+	workspace.resolve().export_to(output.join("sub/..")).unwrap();
+
+	assert!(output.join("pack.mcmeta").is_file());
+	assert!(!output.join("sub").exists());
+}
+
+#[test]
+fn lenient_export_collect_failures() {
+	let output = output_dir("lenient_export_collect_failures");
+	let workspace = TestWorkspace::new("tests/export/broken");
+	let report = workspace.resolve().export_to_lenient(&output).unwrap();
+	assert!(!report.is_success());
+
+	let failures = report.failures();
+	assert_eq!(failures.len(), 1);
+	assert_eq!(failures[0].index().path(), Path::new("data/list.txt"));
+	assert_eq!(failures[0].pid(), &Pid::new(0));
+	assert_eq!(failures[0].operation(), Operation::Merge);
+
+	assert!(output.join("pack.mcmeta").is_file());
+	assert!(!output.join("data/list.txt").exists());
+	assert_eq!(report.provenance().len(), 1);
+}
+
+#[test]
+fn unserializable_file_fail_with_index() {
+	let workspace = TestWorkspace::new("tests/export/unserializable");
+	let error = workspace
+		.resolve()
+		.export_with(&mut MemorySink::new())
+		.unwrap_err();
+	match error {
+		Error::File { index, .. } => assert_eq!(index.path(), Path::new("data/bad.txt")),
+		error => panic!("Unexpected error: {}", error),
+	}
+
+	let output = output_dir("unserializable_file_fail_with_index");
+	let report = workspace.resolve().export_to_lenient(&output).unwrap();
+	assert_eq!(report.failures().len(), 1);
+	assert_eq!(report.failures()[0].operation(), Operation::Write);
+	assert!(output.join("data/good.txt").is_file());
+	assert!(!output.join("data/bad.txt").exists());
+}
+
+#[test]
+fn malformed_file_fail_with_index() {
+	let workspace = TestWorkspace::new("tests/export/malformed");
+	let error = workspace
+		.resolve()
+		.export_with(&mut MemorySink::new())
+		.unwrap_err();
+	match error {
+		Error::Load { index, .. } => assert_eq!(index.path(), Path::new("data/binary.txt")),
+		error => panic!("Unexpected error: {}", error),
+	}
+}
+
+#[test]
+fn missing_file_fail_with_index() {
+	let root = output_dir("missing_file_fail_with_index");
+	copy_dir(Path::new("tests/export/valid"), &root);
+	let workspace = TestWorkspace::new(&root);
+	std::fs::remove_file(root.join("beta/data/list.txt")).unwrap();
+
+	let error = workspace
+		.resolve()
+		.export_with(&mut MemorySink::new())
+		.unwrap_err();
+	match error {
+		Error::Load { index, .. } => {
+			assert_eq!(index, Index::new(Pid::new(1), "data/list.txt"))
+		}
+		error => panic!("Unexpected error: {}", error),
+	}
+}
+
+#[test]
+fn pass_through_copy_without_loading() {
+	let output = output_dir("pass_through_copy_without_loading");
+	let workspace = TestWorkspace::new("tests/export/assets");
+	let provenance = workspace.resolve().export_to(&output).unwrap();
+	assert_eq!(provenance.len(), 2);
+
+	let read = |path: &str| std::fs::read(path).unwrap();
+	let stone = std::fs::read(output.join("textures/stone.png")).unwrap();
+	assert_eq!(stone, read("tests/export/assets/alpha/textures/stone.png"));
+	let dirt = std::fs::read(output.join("textures/dirt.png")).unwrap();
+	assert_eq!(dirt, read("tests/export/assets/beta/textures/dirt.png"));
+}
+
+#[test]
+fn pass_through_copy_highest_priority_only() {
+	let mut sink = CountingSink::default();
+	let workspace = TestWorkspace::new("tests/export/assets");
+	let provenance = workspace.resolve().export_with(&mut sink).unwrap();
+
+	assert_eq!(sink.writes[Path::new("textures/stone.png")], 1);
+	let stone = std::fs::read("tests/export/assets/alpha/textures/stone.png").unwrap();
+	assert_eq!(sink.inner.get("textures/stone.png"), Some(&stone[..]));
+
+	let sources = provenance.get("textures/stone.png").unwrap().sources();
+	assert_eq!(sources.len(), 1);
+	assert_eq!(*sources[0].source().pid(), Pid::new(0));
+}
+
+#[test]
+fn pass_through_has_no_relation() {
+	let workspace = TestWorkspace::new("tests/export/assets");
+	let timeline = workspace.resolve();
+	let graph = timeline.relations().unwrap();
+	assert_eq!(graph.len(), 3);
+	assert!(graph.dangling(|_| false).is_empty());
+	assert!(timeline.dangling().unwrap().is_empty());
+
+	let mut sink = MemorySink::new();
+	let shaken = timeline.tree_shake(|_| true).unwrap();
+	shaken.export_with(&mut sink).unwrap();
+	assert_eq!(sink.len(), 2);
+}
+
+#[test]
+fn merge_write_each_output_once() {
+	let mut sink = CountingSink::default();
+	let workspace = TestWorkspace::new("tests/export/valid");
+	workspace.resolve().export_with(&mut sink).unwrap();
+
+	assert_eq!(sink.writes[Path::new("data/list.txt")], 1);
+	assert_eq!(sink.inner.get("data/list.txt"), Some(&b"two\none\n"[..]));
+}
+
+#[test]
+fn merge_all_receive_every_copy() {
+	let mut sink = MemorySink::new();
+	let workspace = TestWorkspace::new("tests/export/tags");
+	workspace.resolve().export_with(&mut sink).unwrap();
+
+	assert_eq!(sink.get("data/tags.txt"), Some(&b"iron\ndirt\ngold\n"[..]));
+	let provenance = workspace
+		.resolve()
+		.export_with(&mut MemorySink::new())
+		.unwrap();
+	assert_eq!(provenance.get("data/tags.txt").unwrap().sources().len(), 3);
+}
+
+#[test]
+fn export_to_match_export_with() {
+	let output = output_dir("export_to_match_export_with");
+	let workspace = TestWorkspace::new("tests/export/tags");
+	let provenance = workspace.resolve().export_to(&output).unwrap();
+
+	let mut sink = MemorySink::new();
+	let expected = workspace.resolve().export_with(&mut sink).unwrap();
+	assert_eq!(provenance, expected);
+
+	for (path, data) in sink.iter() {
+		assert_eq!(&std::fs::read(output.join(path)).unwrap(), data);
+	}
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn export_to_parallel_match_export_with() {
+	let output = output_dir("export_to_parallel_match_export_with");
+	let workspace = TestWorkspace::new("tests/export/tags");
+	let provenance = workspace.resolve().export_to_parallel(&output).unwrap();
+
+	let mut sink = MemorySink::new();
+	let expected = workspace.resolve().export_with(&mut sink).unwrap();
+	assert_eq!(provenance, expected);
+
+	for (path, data) in sink.iter() {
+		assert_eq!(&std::fs::read(output.join(path)).unwrap(), data);
+	}
+}
+
+#[test]
+fn export_into_memory() {
+	let workspace = TestWorkspace::new("tests/export/valid");
+	let mut sink = MemorySink::new();
+	workspace.resolve().export_with(&mut sink).unwrap();
+
+	let paths: Vec<_> = sink.iter().map(|(path, _)| path.clone()).collect();
+	let expect: Vec<PathBuf> = vec![
+		"data/alpha.txt".into(),
+		"data/list.txt".into(),
+		"pack.mcmeta".into(),
+	];
+	assert_eq!(paths, expect);
+
+	let list = String::from_utf8(sink.get("data/list.txt").unwrap().to_vec()).unwrap();
+	assert!(list.contains("one"));
+	assert!(list.contains("two"));
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn export_zip_is_reproducible() {
+	let output = output_dir("export_zip_is_reproducible");
+	std::fs::create_dir_all(&output).unwrap();
+
+	let first = output.join("first.zip");
+	let second = output.join("second.zip");
+	TestWorkspace::new("tests/export/valid")
+		.resolve()
+		.export_to_zip(&first)
+		.unwrap();
+	TestWorkspace::new("tests/export/valid")
+		.resolve()
+		.export_to_zip(&second)
+		.unwrap();
+
+	let first = std::fs::read(first).unwrap();
+	let second = std::fs::read(second).unwrap();
+	assert_eq!(first, second);
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn zip_entry_can_not_escape_output() {
+	use std::io::Write;
+	use superfusion::project::ZipProject;
+	use superfusion::sink::DirSink;
+
+	let output = output_dir("zip_entry_can_not_escape_output");
+	std::fs::create_dir_all(&output).unwrap();
+
+	let archive = output.join("escape.zip");
+	let mut writer = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+	writer
+		.start_file("../escaped.txt", zip::write::FileOptions::default())
+		.unwrap();
+	writer.write_all(b"escaped\n").unwrap();
+	writer.finish().unwrap();
+
+	match ZipProject::from_archive(&archive, Pid::new(0)) {
+		Err(Error::UnsafePath { path }) => assert_eq!(path, Path::new("../escaped.txt")),
+		Err(error) => panic!("Unexpected error: {}", error),
+		Ok(_) => panic!("Archive with an escaping entry was opened"),
+	}
+
+	let mut sink = DirSink::new(output.join("pack"));
+	assert!(sink.write(Path::new("../escaped.txt"), b"escaped\n").is_err());
+	assert!(sink.write(&output.join("escaped.txt"), b"escaped\n").is_err());
+	assert!(!output.join("escaped.txt").exists());
+}
+
+#[cfg(feature = "zip")]
+#[test]
+fn export_from_zip_project() {
+	use superfusion::project::ZipProject;
+
+	let output = output_dir("export_from_zip_project");
+	std::fs::create_dir_all(&output).unwrap();
+
+	let archive = output.join("plain.zip");
+	TestWorkspace::new("tests/export/plain")
+		.resolve()
+		.export_to_zip(&archive)
+		.unwrap();
+
+	let project = ZipProject::from_archive(&archive, Pid::new(0)).unwrap();
+	let workspace = TestWorkspace {
+		projects: vec![project],
+		priority: Vec::new(),
+		content_aware: true,
+	};
+	let mut sink = MemorySink::new();
+	workspace.resolve().export_with(&mut sink).unwrap();
+
+	let mut expect = MemorySink::new();
+	TestWorkspace::new("tests/export/plain")
+		.resolve()
+		.export_with(&mut expect)
+		.unwrap();
+	assert_eq!(sink, expect);
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+	for entry in walkdir::WalkDir::new(from) {
+		let entry = entry.unwrap();
+		let target = to.join(entry.path().strip_prefix(from).unwrap());
+		if entry.file_type().is_dir() {
+			std::fs::create_dir_all(target).unwrap();
+		} else {
+			std::fs::copy(entry.path(), target).unwrap();
+		}
+	}
+}
+
+#[test]
+fn incremental_export_only_rewrite_changes() {
+	let root = output_dir("incremental_export_only_rewrite_changes");
+	let projects = root.join("projects");
+	let output = root.join("output");
+	let manifest = root.join("manifest");
+	copy_dir(Path::new("tests/export/valid"), &projects);
+
+	let export = || {
+		TestWorkspace::new(&projects)
+			.resolve()
+			.export_incremental(&output, &manifest)
+			.unwrap()
+	};
+	let modified = |path: &str| {
+		std::fs::metadata(output.join(path))
+			.unwrap()
+			.modified()
+			.unwrap()
+	};
+
+	export();
+	let list = std::fs::read_to_string(output.join("data/list.txt")).unwrap();
+	assert_eq!(list, "two\none\n");
+	assert!(output.join("data/alpha.txt").is_file());
+	let pack = modified("pack.mcmeta");
+
+	std::fs::write(projects.join("beta/data/list.txt"), "three\n").unwrap();
+	std::fs::remove_file(projects.join("alpha/data/alpha.txt")).unwrap();
+
+	export();
+	let list = std::fs::read_to_string(output.join("data/list.txt")).unwrap();
+	assert_eq!(list, "three\none\n");
+	assert!(!output.join("data/alpha.txt").exists());
+	assert_eq!(modified("pack.mcmeta"), pack);
+}